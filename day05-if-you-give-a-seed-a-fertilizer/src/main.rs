@@ -1,6 +1,11 @@
-use std::{collections::HashMap, io::Write, iter::once};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    io::Write,
+    iter::once,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Mapping {
     dest_start: u64,
     src_start: u64,
@@ -16,6 +21,22 @@ impl Mapping {
         }
     }
 
+    /// Like `new`, but `None` if the source or destination range would run past `u64::MAX`.
+    fn try_new(dest_start: u64, src_start: u64, len: u64) -> Option<Self> {
+        dest_start.checked_add(len)?;
+        Some(Self {
+            dest_start,
+            src_start,
+            src_end: src_start.checked_add(len)?,
+        })
+    }
+
+    /// Like `new`, but cut short so that neither range runs past `u64::MAX`.
+    fn clamped(dest_start: u64, src_start: u64, len: u64) -> Self {
+        let len = len.min(u64::MAX - dest_start).min(u64::MAX - src_start);
+        Self::new(dest_start, src_start, len)
+    }
+
     fn map(&self, src: u64) -> Option<u64> {
         if src < self.src_start || src >= self.src_end {
             None
@@ -24,45 +45,377 @@ impl Mapping {
             Some(self.dest_start + delta)
         }
     }
+
+    fn dest_end(&self) -> u64 {
+        self.dest_start + (self.src_end - self.src_start)
+    }
+}
+
+/// A mapping table whose entries are sorted by source and don't overlap, so a lookup is a binary
+/// search and two tables can be composed into one. Like in the almanac, any source that isn't
+/// covered by an entry maps to itself.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct PiecewiseMap {
+    mappings: Vec<Mapping>,
+}
+
+impl PiecewiseMap {
+    fn new(mappings: &[Mapping]) -> Self {
+        // the almanac uses the first entry that matches, so a later entry only keeps the parts
+        // of its source range that no earlier entry covers
+        let mut disjoint: Vec<Mapping> = Vec::new();
+        for mapping in mappings {
+            let mut ranges = vec![(mapping.src_start, mapping.src_end)];
+            for taken in disjoint.iter() {
+                ranges = ranges
+                    .into_iter()
+                    .flat_map(|(start, end)| {
+                        let before = (start, taken.src_start.min(end));
+                        let after = (taken.src_end.max(start), end);
+                        [before, after].into_iter().filter(|(s, e)| s < e)
+                    })
+                    .collect();
+            }
+            disjoint.extend(ranges.into_iter().map(|(start, end)| {
+                let dest_start = mapping.dest_start + (start - mapping.src_start);
+                Mapping::new(dest_start, start, end - start)
+            }));
+        }
+        Self::from_disjoint(disjoint)
+    }
+
+    fn from_disjoint(mut mappings: Vec<Mapping>) -> Self {
+        mappings.sort_unstable_by_key(|mapping| mapping.src_start);
+
+        // identity entries are implied, and neighbours that carry on from each other can merge
+        let mut merged: Vec<Mapping> = Vec::with_capacity(mappings.len());
+        for mapping in mappings
+            .into_iter()
+            .filter(|mapping| mapping.src_start < mapping.src_end)
+            .filter(|mapping| mapping.src_start != mapping.dest_start)
+        {
+            match merged.last_mut() {
+                Some(prev)
+                    if prev.src_end == mapping.src_start
+                        && prev.dest_end() == mapping.dest_start =>
+                {
+                    prev.src_end = mapping.src_end;
+                }
+                _ => merged.push(mapping),
+            }
+        }
+        Self { mappings: merged }
+    }
+
+    fn find(&self, src: u64) -> Option<&Mapping> {
+        let i = self
+            .mappings
+            .partition_point(|mapping| mapping.src_end <= src);
+        self.mappings
+            .get(i)
+            .filter(|mapping| mapping.src_start <= src)
+    }
+
+    fn map(&self, src: u64) -> u64 {
+        self.find(src)
+            .and_then(|mapping| mapping.map(src))
+            .unwrap_or(src)
+    }
+
+    /// Every source that ends up at `dest`. An entry can land on a range that's also left
+    /// unmapped, so there may be several of them, or none at all.
+    fn invert(&self, dest: u64) -> Vec<u64> {
+        let mut srcs: Vec<u64> = self
+            .mappings
+            .iter()
+            .filter(|mapping| mapping.dest_start <= dest && dest < mapping.dest_end())
+            .map(|mapping| mapping.src_start + (dest - mapping.dest_start))
+            .collect();
+        if self.find(dest).is_none() {
+            srcs.push(dest);
+        }
+        srcs.sort_unstable();
+        srcs
+    }
+
+    /// Splits the source range `[start, end)` into the pieces of this table it touches,
+    /// including the gaps between entries, which map to themselves.
+    fn pieces(&self, start: u64, end: u64) -> Vec<Mapping> {
+        let mut pieces = Vec::new();
+        let mut cur = start;
+        let first = self
+            .mappings
+            .partition_point(|mapping| mapping.src_end <= start);
+        for mapping in self.mappings[first..].iter() {
+            if mapping.src_start >= end {
+                break;
+            }
+            if cur < mapping.src_start {
+                pieces.push(Mapping::new(cur, cur, mapping.src_start - cur));
+                cur = mapping.src_start;
+            }
+            let piece_end = mapping.src_end.min(end);
+            let dest_start = mapping.dest_start + (cur - mapping.src_start);
+            pieces.push(Mapping::new(dest_start, cur, piece_end - cur));
+            cur = piece_end;
+        }
+        if cur < end {
+            pieces.push(Mapping::new(cur, cur, end - cur));
+        }
+        pieces
+    }
+
+    /// The table that does `self` and then `next` in a single lookup.
+    fn compose(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        // u64::MAX itself can't be inside any entry, so it always maps to itself
+        let composed = self
+            .pieces(0, u64::MAX)
+            .into_iter()
+            .flat_map(|piece| {
+                next.pieces(piece.dest_start, piece.dest_end())
+                    .into_iter()
+                    .map(move |next_piece| {
+                        let src_start = piece.src_start + (next_piece.src_start - piece.dest_start);
+                        let len = next_piece.src_end - next_piece.src_start;
+                        Mapping::new(next_piece.dest_start, src_start, len)
+                    })
+            })
+            .collect();
+        Self::from_disjoint(composed)
+    }
+}
+
+impl Display for PiecewiseMap {
+    /// Writes the entries as `<dest> <src> <len>` lines, same as the almanac.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for mapping in self.mappings.iter() {
+            let len = mapping.src_end - mapping.src_start;
+            writeln!(f, "{} {} {}", mapping.dest_start, mapping.src_start, len)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
 struct Almanac {
     seeds: Vec<u64>,
-    mappings: HashMap<MappingOp, Vec<Mapping>>,
+    stages: Vec<Stage>,
+}
+
+/// One `<from>-to-<to> map:` section of the almanac.
+#[derive(Debug)]
+struct Stage {
+    from: String,
+    to: String,
+    /// `[dest, src, len]` exactly as written, so `validate` can report on entries that don't
+    /// make sense as mappings
+    entries: Vec<[u64; 3]>,
+}
+
+impl Stage {
+    /// The entries as mappings, with any that run past `u64::MAX` cut short there. `validate`
+    /// reports those as problems.
+    fn mappings(&self) -> Vec<Mapping> {
+        self.entries
+            .iter()
+            .map(|&[dest_start, src_start, len]| Mapping::clamped(dest_start, src_start, len))
+            .collect()
+    }
+
+    fn validate(&self) -> Validation {
+        let mut problems = Vec::new();
+
+        // entries are numbered from 1, like lines in the section
+        let mut valid = Vec::new();
+        for (i, &[dest_start, src_start, len]) in self.entries.iter().enumerate() {
+            match Mapping::try_new(dest_start, src_start, len) {
+                Some(mapping) => valid.push((i + 1, mapping)),
+                None => problems.push(Problem::Overflow { entry: i + 1 }),
+            }
+        }
+
+        for (i, (first, a)) in valid.iter().enumerate() {
+            for (second, b) in valid[i + 1..].iter() {
+                let entries = (*first, *second);
+                let (start, end) = (a.src_start.max(b.src_start), a.src_end.min(b.src_end));
+                if start < end {
+                    problems.push(Problem::SourceOverlap {
+                        entries,
+                        start,
+                        end,
+                    });
+                }
+                let (start, end) = (
+                    a.dest_start.max(b.dest_start),
+                    a.dest_end().min(b.dest_end()),
+                );
+                if start < end {
+                    problems.push(Problem::DestinationOverlap {
+                        entries,
+                        start,
+                        end,
+                    });
+                }
+            }
+        }
+
+        let mut ranges = valid
+            .iter()
+            .map(|(_, mapping)| (mapping.src_start, mapping.src_end))
+            .filter(|(start, end)| start < end)
+            .collect::<Vec<_>>();
+        ranges.sort_unstable();
+        let mut coverage = Coverage {
+            entries: self.entries.len(),
+            covered: 0,
+            span: ranges
+                .first()
+                .map(|(start, _)| (*start, ranges.iter().map(|(_, end)| *end).max().unwrap())),
+            gaps: 0,
+        };
+        let mut covered_until = None;
+        for (start, end) in ranges {
+            let start = match covered_until {
+                Some(until) if start > until => {
+                    coverage.gaps += 1;
+                    start
+                }
+                Some(until) => until,
+                None => start,
+            };
+            if start < end {
+                coverage.covered += end - start;
+                covered_until = Some(end);
+            }
+        }
+
+        Validation { problems, coverage }
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-enum MappingOp {
-    None,
-    SeedToSoil,
-    SoilToFertilizer,
-    FertilizerToWater,
-    WaterToLight,
-    LightToTemperature,
-    TemperatureToHumidity,
-    HumidityToLocation,
+#[derive(Debug, PartialEq, Eq)]
+enum Problem {
+    /// both entries claim some of the same sources, and the first one wins
+    SourceOverlap {
+        entries: (usize, usize),
+        start: u64,
+        end: u64,
+    },
+    /// both entries land on some of the same destinations, so the stage isn't injective
+    DestinationOverlap {
+        entries: (usize, usize),
+        start: u64,
+        end: u64,
+    },
+    /// the entry's source or destination range runs past `u64::MAX`
+    Overflow { entry: usize },
 }
 
-impl MappingOp {
-    fn next(&self) -> Self {
-        use MappingOp::*;
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            None => SeedToSoil,
-            SeedToSoil => SoilToFertilizer,
-            SoilToFertilizer => FertilizerToWater,
-            FertilizerToWater => WaterToLight,
-            WaterToLight => LightToTemperature,
-            LightToTemperature => TemperatureToHumidity,
-            TemperatureToHumidity => HumidityToLocation,
-            HumidityToLocation => None,
+            Problem::SourceOverlap {
+                entries: (first, second),
+                start,
+                end,
+            } => write!(
+                f,
+                "entries {} and {} overlap on sources {}..{}",
+                first, second, start, end
+            ),
+            Problem::DestinationOverlap {
+                entries: (first, second),
+                start,
+                end,
+            } => write!(
+                f,
+                "entries {} and {} overlap on destinations {}..{}",
+                first, second, start, end
+            ),
+            Problem::Overflow { entry } => write!(f, "entry {} runs past u64::MAX", entry),
         }
     }
 }
 
-fn parse_input(text: &str) -> Almanac {
-    use MappingOp::*;
+#[derive(Debug, PartialEq, Eq)]
+struct Coverage {
+    entries: usize,
+    /// how many sources some entry maps
+    covered: u64,
+    /// from the lowest mapped source up to (not including) the end of the highest
+    span: Option<(u64, u64)>,
+    /// unmapped holes inside the span
+    gaps: usize,
+}
+
+impl Display for Coverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.span {
+            Some((start, end)) => write!(
+                f,
+                "{} entries, {} sources mapped in {}..{} ({:.1}%), {} gaps",
+                self.entries,
+                self.covered,
+                start,
+                end,
+                100.0 * self.covered as f64 / (end - start) as f64,
+                self.gaps
+            ),
+            None => write!(f, "{} entries, nothing mapped", self.entries),
+        }
+    }
+}
 
+#[derive(Debug)]
+struct Validation {
+    problems: Vec<Problem>,
+    coverage: Coverage,
+}
+
+impl Almanac {
+    /// Finds the shortest chain of stages that leads from the `from` category to the `to` one.
+    fn path<'a>(&'a self, from: &'a str, to: &str) -> Option<Vec<&'a Stage>> {
+        let mut reached_by: HashMap<&str, Option<&Stage>> = HashMap::from([(from, None)]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                let mut path = Vec::new();
+                let mut cur = category;
+                while let Some(Some(stage)) = reached_by.get(cur) {
+                    path.push(*stage);
+                    cur = &stage.from;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for stage in self.stages.iter().filter(|stage| stage.from == category) {
+                if !reached_by.contains_key(stage.to.as_str()) {
+                    reached_by.insert(&stage.to, Some(stage));
+                    queue.push_back(&stage.to);
+                }
+            }
+        }
+        None
+    }
+
+    /// Composes the stages between two categories into a single table.
+    fn resolve(&self, from: &str, to: &str) -> Option<PiecewiseMap> {
+        let path = self.path(from, to)?;
+        let composed = path
+            .iter()
+            .fold(PiecewiseMap::default(), |composed, stage| {
+                composed.compose(&PiecewiseMap::new(&stage.mappings()))
+            });
+        Some(composed)
+    }
+
+    fn seed_to_location(&self) -> PiecewiseMap {
+        self.resolve("seed", "location")
+            .expect("should have found a path from seed to location")
+    }
+}
+
+fn parse_input(text: &str) -> Almanac {
     // add an empty line to the end so we can detect the end of the last mapping
     let mut lines = text.lines().chain(once(""));
 
@@ -79,32 +432,24 @@ fn parse_input(text: &str) -> Almanac {
         .map(|seed| seed.trim().parse().expect("should have found a number"))
         .collect();
 
-    let mut mappings = HashMap::new();
-    let mut cur_mapping_name = MappingOp::None;
-    let mut cur_mappings: Vec<Mapping> = Vec::new();
+    let mut stages = Vec::new();
+    let mut cur_stage: Option<Stage> = None;
     for line in lines {
-        if line == "seed-to-soil map:" {
-            cur_mapping_name = SeedToSoil;
-        } else if line == "soil-to-fertilizer map:" {
-            cur_mapping_name = SoilToFertilizer;
-        } else if line == "fertilizer-to-water map:" {
-            cur_mapping_name = FertilizerToWater;
-        } else if line == "water-to-light map:" {
-            cur_mapping_name = WaterToLight;
-        } else if line == "light-to-temperature map:" {
-            cur_mapping_name = LightToTemperature;
-        } else if line == "temperature-to-humidity map:" {
-            cur_mapping_name = TemperatureToHumidity;
-        } else if line == "humidity-to-location map:" {
-            cur_mapping_name = HumidityToLocation;
+        if let Some(header) = line.strip_suffix(" map:") {
+            let (from, to) = header
+                .split_once("-to-")
+                .unwrap_or_else(|| panic!("should have found `<a>-to-<b> map:`, got {:?}", line));
+            cur_stage = Some(Stage {
+                from: from.to_string(),
+                to: to.to_string(),
+                entries: Vec::new(),
+            });
         } else if line.is_empty() {
-            if cur_mapping_name != None {
-                mappings.insert(cur_mapping_name, cur_mappings);
-                cur_mapping_name = None;
-                cur_mappings = Vec::new();
+            if let Some(stage) = cur_stage.take() {
+                stages.push(stage);
             }
         } else {
-            // if it's not one of the headers, it must be the 3-number mapping line
+            // if it's not a header, it must be the 3-number mapping line
             let mut parts = line.split_whitespace();
             let dest_start: u64 = parts
                 .next()
@@ -118,35 +463,171 @@ fn parse_input(text: &str) -> Almanac {
                 .next()
                 .and_then(|x| x.trim().parse().ok())
                 .expect("should have found a length");
-            let mapping = Mapping::new(dest_start, src_start, len);
-            cur_mappings.push(mapping);
+            cur_stage
+                .as_mut()
+                .expect("should have found a `<a>-to-<b> map:` header before the mappings")
+                .entries
+                .push([dest_start, src_start, len]);
         }
     }
-    Almanac { seeds, mappings }
+    Almanac { seeds, stages }
+}
+
+#[test]
+fn test_compose_matches_stepwise() {
+    let almanac = parse_input(include_str!("sample.txt"));
+    let seed_to_location = almanac.seed_to_location();
+    for seed_num in 0..200 {
+        let mut cur_src = seed_num;
+        for stage in almanac.path("seed", "location").unwrap() {
+            let dest = stage
+                .mappings()
+                .iter()
+                .find_map(|mapping| mapping.map(cur_src));
+            cur_src = dest.unwrap_or(cur_src);
+        }
+        assert_eq!(seed_to_location.map(seed_num), cur_src, "seed {}", seed_num);
+        assert!(seed_to_location.invert(cur_src).contains(&seed_num));
+    }
+}
+
+#[test]
+fn test_compose_overlapping_entries() {
+    // the second entry overlaps the first, which wins for 10..15
+    let first = PiecewiseMap::new(&[Mapping::new(100, 10, 5), Mapping::new(200, 12, 6)]);
+    assert_eq!(first.map(12), 102);
+    assert_eq!(first.map(15), 203);
+    assert_eq!(first.map(18), 18);
+
+    let second = PiecewiseMap::new(&[Mapping::new(10, 100, 5)]);
+    let composed = first.compose(&second);
+    assert_eq!(composed.map(10), 10);
+    assert_eq!(composed.map(14), 14);
+    assert_eq!(composed.map(16), 204);
+    assert_eq!(composed.map(100), 10);
+    assert_eq!(composed.invert(10), vec![10, 100]);
+    assert_eq!(composed.invert(102), Vec::<u64>::new());
+    assert_eq!(composed.to_string(), "203 15 3\n10 100 5\n");
+}
+
+#[test]
+fn test_resolve_reordered_stages() {
+    // stages out of order, plus an extra one that isn't on the way to location
+    let text = "seeds: 1 2 3\n\
+        \n\
+        soil-to-water map:\n\
+        10 0 5\n\
+        \n\
+        seed-to-soil map:\n\
+        0 1 3\n\
+        \n\
+        water-to-location map:\n\
+        100 10 2\n\
+        \n\
+        seed-to-color map:\n\
+        7 1 1\n";
+    let almanac = parse_input(text);
+    assert_eq!(almanac.stages.len(), 4);
+
+    let path = almanac.path("seed", "location").unwrap();
+    let names = path
+        .iter()
+        .map(|stage| format!("{}-to-{}", stage.from, stage.to))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["seed-to-soil", "soil-to-water", "water-to-location"]
+    );
+
+    let seed_to_water = almanac.resolve("seed", "water").unwrap();
+    assert_eq!(seed_to_water.map(1), 10);
+    assert_eq!(seed_to_water.map(3), 12);
+    assert_eq!(seed_to_water.map(5), 5);
+    let soil_to_location = almanac.resolve("soil", "location").unwrap();
+    assert_eq!(soil_to_location.map(1), 101);
+    assert_eq!(almanac.seed_to_location().map(1), 100);
+    assert!(almanac.resolve("location", "seed").is_none());
+    assert!(almanac.resolve("color", "location").is_none());
+}
+
+#[test]
+fn test_validate() {
+    let almanac = parse_input(include_str!("sample.txt"));
+    for stage in almanac.stages.iter() {
+        assert_eq!(stage.validate().problems, vec![], "{}", stage.from);
+    }
+    let seed_to_soil = almanac.stages[0].validate();
+    assert_eq!(
+        seed_to_soil.coverage,
+        Coverage {
+            entries: 2,
+            covered: 50,
+            span: Some((50, 100)),
+            gaps: 0,
+        }
+    );
+
+    let text = "seeds: 1\n\
+        \n\
+        seed-to-soil map:\n\
+        100 10 10\n\
+        0 15 10\n\
+        105 40 2\n\
+        0 18446744073709551615 2\n";
+    let validation = parse_input(text).stages[0].validate();
+    assert_eq!(
+        validation.problems,
+        vec![
+            Problem::Overflow { entry: 4 },
+            Problem::SourceOverlap {
+                entries: (1, 2),
+                start: 15,
+                end: 20,
+            },
+            Problem::DestinationOverlap {
+                entries: (1, 3),
+                start: 105,
+                end: 107,
+            },
+        ]
+    );
+    assert_eq!(
+        validation.coverage,
+        Coverage {
+            entries: 4,
+            covered: 17,
+            span: Some((10, 42)),
+            gaps: 1,
+        }
+    );
+}
+
+#[test]
+fn test_overflowing_entry_is_clamped() {
+    let text = "seeds: 1\n\
+        \n\
+        seed-to-location map:\n\
+        0 18446744073709551610 10\n\
+        18446744073709551614 0 5\n";
+    let seed_to_location = parse_input(text).seed_to_location();
+    assert_eq!(seed_to_location.map(u64::MAX - 5), 0);
+    assert_eq!(seed_to_location.map(u64::MAX - 1), 4);
+    assert_eq!(seed_to_location.map(u64::MAX), u64::MAX);
+    assert_eq!(seed_to_location.map(0), u64::MAX - 1);
+    assert_eq!(seed_to_location.map(1), 1);
 }
 
 fn part1() {
     // let (text, expected_min) = (include_str!("sample.txt"), Some(35));
     let (text, expected_min) = (include_str!("my_input.txt"), None);
     let almanac = parse_input(text);
+    let seed_to_location = almanac.seed_to_location();
     let mut destinations = Vec::new();
 
     for seed_num in almanac.seeds {
-        let mut cur_mapping_operation = MappingOp::SeedToSoil;
-        let mut cur_src = seed_num;
-        while cur_mapping_operation != MappingOp::None {
-            let mappings = almanac.mappings.get(&cur_mapping_operation).unwrap();
-            let dest = mappings
-                .iter()
-                .filter_map(|mapping| mapping.map(cur_src))
-                .next();
-            let next_src = dest.unwrap_or(cur_src);
-
-            cur_mapping_operation = cur_mapping_operation.next();
-            cur_src = next_src;
-        }
-        println!("seed {} ends up at {}", seed_num, cur_src);
-        destinations.push(cur_src);
+        let location = seed_to_location.map(seed_num);
+        println!("seed {} ends up at {}", seed_num, location);
+        destinations.push(location);
     }
 
     let min = *destinations
@@ -167,6 +648,7 @@ fn part2() {
     let (text, expected_min) = (include_str!("my_input.txt"), Some(24261545));
 
     let almanac = parse_input(text);
+    let seed_to_location = almanac.seed_to_location();
     let seed_ranges = almanac
         .seeds
         .par_chunks(2)
@@ -181,16 +663,7 @@ fn part2() {
                 print!(".");
                 std::io::stdout().flush().unwrap();
             }
-            let mut cur_mapping_operation = MappingOp::SeedToSoil;
-            let mut cur_src = seed_num;
-            while cur_mapping_operation != MappingOp::None {
-                let mappings = almanac.mappings.get(&cur_mapping_operation).unwrap();
-                let dest = mappings.iter().find_map(|mapping| mapping.map(cur_src));
-                let next_src = dest.unwrap_or(cur_src);
-                cur_mapping_operation = cur_mapping_operation.next();
-                cur_src = next_src;
-            }
-            cur_src
+            seed_to_location.map(seed_num)
         })
         .min();
 
@@ -201,10 +674,66 @@ fn part2() {
     }
 }
 
+/// Prints the composed table between two categories, in the same format as the almanac.
+fn dump(from: &str, to: &str) {
+    let text = include_str!("my_input.txt");
+    let almanac = parse_input(text);
+    let composed = almanac
+        .resolve(from, to)
+        .unwrap_or_else(|| panic!("should have found a path from {} to {}", from, to));
+    println!("{}-to-{} map:", from, to);
+    print!("{}", composed);
+}
+
+/// Checks every stage of the almanac and prints what it found. Exits with an error if there
+/// were any problems.
+fn validate() {
+    let text = include_str!("my_input.txt");
+    let almanac = parse_input(text);
+    let mut num_problems = 0;
+    for stage in almanac.stages.iter() {
+        let validation = stage.validate();
+        println!("{}-to-{}: {}", stage.from, stage.to, validation.coverage);
+        for problem in validation.problems.iter() {
+            println!("    {}", problem);
+        }
+        num_problems += validation.problems.len();
+    }
+    println!("{} problems found", num_problems);
+    if num_problems > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Prints every seed that ends up at `location`.
+fn invert(location: u64) {
+    let text = include_str!("my_input.txt");
+    let almanac = parse_input(text);
+    let seeds = almanac.seed_to_location().invert(location);
+    println!("location {} comes from seeds {:?}", location, seeds);
+}
+
 fn main() {
-    part1();
-    let start_time = std::time::Instant::now();
-    part2();
-    let end_time = std::time::Instant::now();
-    println!("elapsed time: {:?}", end_time - start_time);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("dump") => match &args[1..] {
+            [from, to] => dump(from, to),
+            _ => dump("seed", "location"),
+        },
+        Some("validate") => validate(),
+        Some("invert") => {
+            let location = args
+                .get(1)
+                .and_then(|x| x.parse().ok())
+                .expect("should have found `invert <location>`");
+            invert(location);
+        }
+        _ => {
+            part1();
+            let start_time = std::time::Instant::now();
+            part2();
+            let end_time = std::time::Instant::now();
+            println!("elapsed time: {:?}", end_time - start_time);
+        }
+    }
 }
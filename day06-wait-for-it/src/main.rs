@@ -1,43 +1,499 @@
-fn quadratic_formula(a: f64, b: f64, c: f64) -> (f64, f64) {
-    let discriminant = b * b - 4.0 * a * c;
-    let x1 = (-b + discriminant.sqrt()) / (2.0 * a);
-    let x2 = (-b - discriminant.sqrt()) / (2.0 * a);
-    (x2, x1)
-}
-
-fn calc_button_limits(time_limit: u64, distance_record: u64) -> (u64, u64) {
-    let time_limit = time_limit as f64;
-    let distance_record = distance_record as f64;
-    let (min, max) = quadratic_formula(1.0, -time_limit, distance_record);
-    ((min + 0.05).ceil() as u64, (max - 0.05).floor() as u64)
-}
-
-fn part1() {
-    use std::iter::zip;
-
-    fn parse_input(input: &str) -> (Vec<u64>, Vec<u64>) {
-        fn parse_line(line: &str) -> Vec<u64> {
-            line.split(':')
-                .nth(1)
-                .expect("should have found <header>: <list of integers>")
-                .split_whitespace()
-                .flat_map(|x| x.parse().ok())
-                .collect()
+use std::{fmt::Display, iter::zip, str::FromStr};
+
+/// The shortest and longest button holds that beat the distance record, or `None` if none do.
+///
+/// A hold of `h` goes `h * (time_limit - h)`, so the winners lie strictly between the roots of
+/// `h^2 - time_limit * h + distance_record`. Everything is done in integers so that it's exact
+/// for any `u64` inputs.
+fn calc_button_limits(time_limit: u64, distance_record: u64) -> Option<(u64, u64)> {
+    let beats_record =
+        |hold: u64| u128::from(hold) * u128::from(time_limit - hold) > u128::from(distance_record);
+
+    let time = u128::from(time_limit);
+    let discriminant = (time * time).checked_sub(4 * u128::from(distance_record))?;
+    let root = discriminant.isqrt();
+
+    // these are within one of the real roots, so nudge them until they're right on the edge
+    let mut min = ((time - root) / 2) as u64;
+    let mut max = ((time + root) / 2) as u64;
+    while min <= max && !beats_record(min) {
+        min += 1;
+    }
+    if min > max {
+        return None;
+    }
+    while min > 0 && beats_record(min - 1) {
+        min -= 1;
+    }
+    while !beats_record(max) {
+        max -= 1;
+    }
+    while max < time_limit && beats_record(max + 1) {
+        max += 1;
+    }
+    Some((min, max))
+}
+
+fn num_ways_to_win(time_limit: u64, distance_record: u64) -> u64 {
+    calc_button_limits(time_limit, distance_record).map_or(0, |(min, max)| max - min + 1)
+}
+
+#[test]
+fn test_calc_button_limits_matches_brute_force() {
+    for time_limit in 0..80_u64 {
+        for distance_record in 0..=(time_limit * time_limit / 4 + 2) {
+            let winners = (0..=time_limit)
+                .filter(|hold| hold * (time_limit - hold) > distance_record)
+                .collect::<Vec<_>>();
+            let expected = winners.first().map(|&min| (min, *winners.last().unwrap()));
+            assert_eq!(
+                calc_button_limits(time_limit, distance_record),
+                expected,
+                "time {} distance {}",
+                time_limit,
+                distance_record
+            );
+        }
+    }
+}
+
+#[test]
+fn test_calc_button_limits_exact() {
+    // roots at exactly 10 and 20, which don't win
+    assert_eq!(calc_button_limits(30, 200), Some((11, 19)));
+    assert_eq!(calc_button_limits(7, 9), Some((2, 5)));
+
+    // too big for f64 to tell the holds apart
+    let distance = |time_limit: u64, hold: u64| u128::from(hold) * u128::from(time_limit - hold);
+    for (time_limit, distance_record) in [
+        (u64::MAX, u64::MAX),
+        (u64::MAX, 12345),
+        ((1 << 33) + 2, u64::MAX),
+        ((1 << 33) + 3, u64::MAX - 1),
+    ] {
+        let (min, max) = calc_button_limits(time_limit, distance_record).unwrap();
+        let record = u128::from(distance_record);
+        assert!(distance(time_limit, min) > record && distance(time_limit, min - 1) <= record);
+        assert!(distance(time_limit, max) > record && distance(time_limit, max + 1) <= record);
+        assert_eq!(min + max, time_limit);
+    }
+    assert_eq!(calc_button_limits((1 << 33) - 1, u64::MAX), None);
+}
+
+/// The rules for how holding the button turns into distance. The default is the puzzle's: one
+/// mm/ms of speed per ms held, and nothing slowing the boat down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RaceModel {
+    /// speed gained for each ms the button is held
+    acceleration: u64,
+    /// the speed only goes up every this many ms, in one go, so a partial step is wasted time
+    charge_step: u64,
+    /// holding the button for longer than this doesn't charge the boat any more
+    hold_cap: Option<u64>,
+    /// taken off the speed for the whole race, so a slow boat doesn't move at all
+    drag: u64,
+}
+
+impl Default for RaceModel {
+    fn default() -> Self {
+        Self {
+            acceleration: 1,
+            charge_step: 1,
+            hold_cap: None,
+            drag: 0,
+        }
+    }
+}
+
+/// A run of holds that `distance` treats the same way.
+#[derive(Debug, Clone, Copy)]
+enum Piece {
+    /// every extra ms held adds speed, so the distance is a hump
+    Charging { start: u64, end: u64 },
+    /// the speed doesn't change, so the distance only goes down
+    Coasting { start: u64, end: u64 },
+}
+
+impl RaceModel {
+    /// Builds a model from `name=value` arguments, starting from the default.
+    fn from_args(args: &[String]) -> Self {
+        let mut model = Self::default();
+        for arg in args {
+            let (name, value) = arg
+                .split_once('=')
+                .unwrap_or_else(|| panic!("should have found <name>=<value>, got {:?}", arg));
+            let value = value
+                .parse()
+                .unwrap_or_else(|_| panic!("should have found an integer for {}", name));
+            match name {
+                "acceleration" => model.acceleration = value,
+                "charge_step" => model.charge_step = value,
+                "hold_cap" => model.hold_cap = Some(value),
+                "drag" => model.drag = value,
+                _ => panic!("unknown race model setting {:?}", name),
+            }
+        }
+        assert!(model.charge_step > 0, "charge_step should be at least 1");
+        model
+    }
+
+    fn speed(&self, hold: u64) -> u128 {
+        let charged = hold - hold % self.charge_step;
+        let charged = self.hold_cap.map_or(charged, |cap| charged.min(cap));
+        u128::from(charged) * u128::from(self.acceleration)
+    }
+
+    fn distance(&self, time_limit: u64, hold: u64) -> u128 {
+        let speed = self.speed(hold).saturating_sub(u128::from(self.drag));
+        speed * u128::from(time_limit - hold)
+    }
+
+    /// Splits `0..=time_limit` into pieces on which the distance goes up and then down (or only
+    /// one of those), so each piece can be searched on its own.
+    fn pieces(&self, time_limit: u64) -> Vec<Piece> {
+        // the speed stops going up at the first step that reaches the cap
+        let charge_end = self.hold_cap.map_or(time_limit, |cap| {
+            let steps = cap.div_ceil(self.charge_step);
+            steps.saturating_mul(self.charge_step).min(time_limit)
+        });
+        let mut pieces = Vec::new();
+        if self.charge_step == 1 {
+            pieces.push(Piece::Charging {
+                start: 0,
+                end: charge_end,
+            });
+        } else {
+            // the speed is flat between steps
+            let mut start = 0;
+            while start < charge_end {
+                let end = start
+                    .saturating_add(self.charge_step - 1)
+                    .min(charge_end - 1);
+                pieces.push(Piece::Coasting { start, end });
+                start = end + 1;
+            }
+        }
+        pieces.push(Piece::Coasting {
+            start: charge_end,
+            end: time_limit,
+        });
+        pieces
+    }
+
+    /// The hold that goes furthest in the piece. The distance never goes down on the way to it,
+    /// and never goes back up after it.
+    fn peak(&self, time_limit: u64, piece: Piece) -> u64 {
+        match piece {
+            Piece::Coasting { start, .. } => start,
+            Piece::Charging { start, end } if self.acceleration == 0 => start.min(end),
+            Piece::Charging { start, end } => {
+                // (a*h - drag) * (T - h) tops out at h = (a*T + drag) / 2a
+                let a = u128::from(self.acceleration);
+                let vertex = (a * u128::from(time_limit) + u128::from(self.drag)) / (2 * a);
+                let vertex = vertex.min(u128::from(end)) as u64;
+                let vertex = vertex.max(start);
+                if vertex < end
+                    && self.distance(time_limit, vertex + 1) > self.distance(time_limit, vertex)
+                {
+                    vertex + 1
+                } else {
+                    vertex
+                }
+            }
+        }
+    }
+}
+
+/// Every run of holds that beats the distance record under the given model, in order. The puzzle
+/// rules give at most one, but stepped charging can give several.
+fn winning_holds(model: &RaceModel, time_limit: u64, distance_record: u64) -> Vec<(u64, u64)> {
+    let record = u128::from(distance_record);
+    let beats_record = |hold: u64| model.distance(time_limit, hold) > record;
+
+    let mut intervals: Vec<(u64, u64)> = Vec::new();
+    for piece in model.pieces(time_limit) {
+        let (Piece::Charging { start, end } | Piece::Coasting { start, end }) = piece;
+        let peak = model.peak(time_limit, piece);
+        if !beats_record(peak) {
+            continue;
+        }
+
+        // the distance only goes up to the peak, so the first winner is a binary search away
+        let (mut lo, mut hi) = (start, peak);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if beats_record(mid) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        let min = lo;
+
+        // and likewise for the last winner on the way back down
+        let (mut lo, mut hi) = (peak, end);
+        while lo < hi {
+            let mid = hi - (hi - lo) / 2;
+            if beats_record(mid) {
+                lo = mid;
+            } else {
+                hi = mid - 1;
+            }
+        }
+        let max = lo;
+
+        match intervals.last_mut() {
+            Some((_, prev_max)) if *prev_max + 1 >= min => *prev_max = max.max(*prev_max),
+            _ => intervals.push((min, max)),
+        }
+    }
+    intervals
+}
+
+fn count_winning_holds(model: &RaceModel, time_limit: u64, distance_record: u64) -> u64 {
+    if *model == RaceModel::default() {
+        return num_ways_to_win(time_limit, distance_record);
+    }
+    let holds = winning_holds(model, time_limit, distance_record);
+    holds.iter().map(|(min, max)| max - min + 1).sum()
+}
+
+/// Shows which holds win each race, unless it's the usual model where that's just one range.
+fn print_winning_holds(model: &RaceModel, races: &[Race]) {
+    if *model == RaceModel::default() {
+        return;
+    }
+    for race in races {
+        let holds = winning_holds(model, race.time_limit, race.distance_record);
+        println!(
+            "  time {} record {}: win by holding {:?}",
+            race.time_limit, race.distance_record, holds
+        );
+    }
+}
+
+#[test]
+fn test_winning_holds_matches_brute_force() {
+    let models = [
+        RaceModel::default(),
+        RaceModel {
+            acceleration: 3,
+            ..Default::default()
+        },
+        RaceModel {
+            hold_cap: Some(4),
+            ..Default::default()
+        },
+        RaceModel {
+            acceleration: 2,
+            drag: 5,
+            ..Default::default()
+        },
+        RaceModel {
+            charge_step: 3,
+            ..Default::default()
+        },
+        RaceModel {
+            acceleration: 2,
+            charge_step: 4,
+            hold_cap: Some(9),
+            drag: 1,
+        },
+    ];
+    for model in models.iter() {
+        for time_limit in 0..40_u64 {
+            let max_distance = (0..=time_limit)
+                .map(|hold| model.distance(time_limit, hold))
+                .max()
+                .unwrap() as u64;
+            for distance_record in 0..=max_distance + 1 {
+                let mut expected: Vec<(u64, u64)> = Vec::new();
+                for hold in 0..=time_limit {
+                    if model.distance(time_limit, hold) > u128::from(distance_record) {
+                        match expected.last_mut() {
+                            Some((_, max)) if *max + 1 == hold => *max = hold,
+                            _ => expected.push((hold, hold)),
+                        }
+                    }
+                }
+                assert_eq!(
+                    winning_holds(model, time_limit, distance_record),
+                    expected,
+                    "{:?} time {} distance {}",
+                    model,
+                    time_limit,
+                    distance_record
+                );
+            }
         }
-        let mut lines = input.lines();
-        let times = parse_line(lines.next().expect("should have found Times:"));
-        let distances = parse_line(lines.next().expect("should have found Distances:"));
-        (times, distances)
     }
+}
+
+#[test]
+fn test_winning_holds_stepped_charging() {
+    let model = RaceModel {
+        charge_step: 5,
+        ..Default::default()
+    };
+    // holding for 5 gets speed 5 and goes 50, 6 only goes 45, 10 goes 50 again
+    assert_eq!(model.distance(15, 5), 50);
+    assert_eq!(model.distance(15, 6), 45);
+    assert_eq!(model.distance(15, 10), 50);
+    assert_eq!(winning_holds(&model, 15, 45), vec![(5, 5), (10, 10)]);
+    assert_eq!(winning_holds(&model, 15, 39), vec![(5, 7), (10, 11)]);
+
+    assert_eq!(
+        winning_holds(&RaceModel::default(), 30, 200),
+        vec![calc_button_limits(30, 200).unwrap()]
+    );
+}
 
+/// How to read the numbers on the sheet of paper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kerning {
+    /// each column is its own race
+    Columns,
+    /// the spaces are just bad kerning, so all the digits make up one race
+    Joined,
+}
+
+impl FromStr for Kerning {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "columns" => Ok(Kerning::Columns),
+            "joined" => Ok(Kerning::Joined),
+            _ => Err(format!(
+                "unknown kerning {:?}, expected columns or joined",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Race {
+    time_limit: u64,
+    distance_record: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ParseError {
+    MissingHeader(&'static str),
+    BadNumber { header: &'static str, text: String },
+    MismatchedColumns { times: usize, distances: usize },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingHeader(header) => {
+                write!(f, "should have found a line starting with {:?}", header)
+            }
+            ParseError::BadNumber { header, text } => {
+                write!(f, "{} has {:?}, which isn't a number", header, text)
+            }
+            ParseError::MismatchedColumns { times, distances } => {
+                write!(f, "found {} times but {} distances", times, distances)
+            }
+        }
+    }
+}
+
+fn parse_races(input: &str, kerning: Kerning) -> Result<Vec<Race>, ParseError> {
+    fn parse_line(
+        input: &str,
+        header: &'static str,
+        kerning: Kerning,
+    ) -> Result<Vec<u64>, ParseError> {
+        let numbers = input
+            .lines()
+            .find_map(|line| line.trim_start().strip_prefix(header))
+            .ok_or(ParseError::MissingHeader(header))?;
+        let parse = |text: &str| {
+            text.parse().map_err(|_| ParseError::BadNumber {
+                header,
+                text: text.to_string(),
+            })
+        };
+        match kerning {
+            Kerning::Columns => numbers.split_whitespace().map(parse).collect(),
+            Kerning::Joined => {
+                let digits = numbers.split_whitespace().collect::<String>();
+                Ok(vec![parse(&digits)?])
+            }
+        }
+    }
+
+    let times = parse_line(input, "Time:", kerning)?;
+    let distances = parse_line(input, "Distance:", kerning)?;
+    if times.len() != distances.len() {
+        return Err(ParseError::MismatchedColumns {
+            times: times.len(),
+            distances: distances.len(),
+        });
+    }
+    let races = zip(times, distances)
+        .map(|(time_limit, distance_record)| Race {
+            time_limit,
+            distance_record,
+        })
+        .collect();
+    Ok(races)
+}
+
+#[test]
+fn test_parse_races() {
+    let input = include_str!("sample.txt");
+    let race = |time_limit, distance_record| Race {
+        time_limit,
+        distance_record,
+    };
+    assert_eq!(
+        parse_races(input, Kerning::Columns),
+        Ok(vec![race(7, 9), race(15, 40), race(30, 200)])
+    );
+    assert_eq!(
+        parse_races(input, Kerning::Joined),
+        Ok(vec![race(71530, 940200)])
+    );
+
+    assert_eq!(
+        parse_races("Time: 7 15\nDistance: 9\n", Kerning::Columns),
+        Err(ParseError::MismatchedColumns {
+            times: 2,
+            distances: 1
+        })
+    );
+    assert_eq!(
+        parse_races("Time: 7 15\nDistance: 9\n", Kerning::Joined),
+        Ok(vec![race(715, 9)])
+    );
+    assert_eq!(
+        parse_races("Time: 7 15\n", Kerning::Columns),
+        Err(ParseError::MissingHeader("Distance:"))
+    );
+    assert_eq!(
+        parse_races("Time: 7 x5\nDistance: 9 40\n", Kerning::Columns),
+        Err(ParseError::BadNumber {
+            header: "Time:",
+            text: "x5".to_string()
+        })
+    );
+}
+
+fn part1(model: &RaceModel) {
     // let (input, expected_prod) = (include_str!("sample.txt"), Some(288_u64));
     let (input, expected_prod) = (include_str!("my_input.txt"), Some(140220_u64));
-    let (times, distances) = parse_input(input);
-    let times_and_distances = zip(times.iter(), distances.iter());
+    let expected_prod = expected_prod.filter(|_| *model == RaceModel::default());
+    let races = parse_races(input, Kerning::Columns)
+        .unwrap_or_else(|err| panic!("couldn't read the races: {}", err));
+    print_winning_holds(model, &races);
 
-    let product = times_and_distances
-        .map(|(&t, &d)| calc_button_limits(t, d))
-        .map(|(min, max)| max - min + 1) // number of solutions
+    let product = races
+        .iter()
+        .map(|race| count_winning_holds(model, race.time_limit, race.distance_record))
         .product();
 
     println!("Part 1: {}", product);
@@ -46,26 +502,15 @@ fn part1() {
     }
 }
 
-fn part2() {
-    fn parse_input(input: &str) -> (u64, u64) {
-        fn parse_line(line: &str) -> u64 {
-            let (_header, rest) = line
-                .split_once(':')
-                .expect("should have found <header>: <list of integers>");
-            rest.replace(' ', "")
-                .parse()
-                .expect("should have found an integer")
-        }
-        let mut lines = input.lines();
-        let time = parse_line(lines.next().expect("should have found Times:"));
-        let distance = parse_line(lines.next().expect("should have found Distances:"));
-        (time, distance)
-    }
+fn part2(model: &RaceModel) {
     // let (input, expected_ways) = (include_str!("sample.txt"), Some(71503_u64));
     let (input, expected_ways) = (include_str!("my_input.txt"), Some(39570185_u64));
-    let (time, distance) = parse_input(input);
-    let (min, max) = calc_button_limits(time, distance);
-    let num_ways_to_win = max - min + 1;
+    let expected_ways = expected_ways.filter(|_| *model == RaceModel::default());
+    let races = parse_races(input, Kerning::Joined)
+        .unwrap_or_else(|err| panic!("couldn't read the races: {}", err));
+    print_winning_holds(model, &races);
+    let race = races[0];
+    let num_ways_to_win = count_winning_holds(model, race.time_limit, race.distance_record);
 
     println!("Part 2: {}", num_ways_to_win);
     if let Some(expected_ways) = expected_ways {
@@ -74,6 +519,22 @@ fn part2() {
 }
 
 fn main() {
-    part1();
-    part2();
+    // e.g. `cargo run -- acceleration=2 hold_cap=30` to race with different rules, and
+    // `kerning=joined` to only read the sheet one way
+    let (kerning_args, model_args): (Vec<_>, Vec<_>) = std::env::args()
+        .skip(1)
+        .partition(|arg| arg.starts_with("kerning="));
+    let model = RaceModel::from_args(&model_args);
+    let kernings = match kerning_args.last() {
+        Some(arg) => vec![arg["kerning=".len()..]
+            .parse()
+            .unwrap_or_else(|err| panic!("{}", err))],
+        None => vec![Kerning::Columns, Kerning::Joined],
+    };
+    for kerning in kernings {
+        match kerning {
+            Kerning::Columns => part1(&model),
+            Kerning::Joined => part2(&model),
+        }
+    }
 }
@@ -0,0 +1,717 @@
+use std::{cmp::Ordering, collections::HashMap, fmt::Display, ops::Range};
+
+/// What changes between variants of Camel Cards: which cards beat which, which card (if any)
+/// can stand in for any other, and how the hand types rank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    /// Every card, weakest first
+    pub card_order: &'static str,
+    pub wild: Option<char>,
+    /// Cards are written with a suit after them, like `Ah`, and straights and flushes count
+    pub suited: bool,
+    /// Every hand type that can come up, weakest first
+    pub ranking: &'static [HandType],
+}
+
+impl Rules {
+    pub const STANDARD: Rules = Rules {
+        card_order: "23456789TJQKA",
+        wild: None,
+        suited: false,
+        ranking: &[
+            HandType::HighCard,
+            HandType::OnePair,
+            HandType::TwoPairs,
+            HandType::ThreeOfAKind,
+            HandType::FullHouse,
+            HandType::FourOfAKind,
+            HandType::FiveOfAKind,
+        ],
+    };
+
+    /// J is a joker now, and the weakest card
+    pub const JOKERS: Rules = Rules {
+        card_order: "J23456789TQKA",
+        wild: Some('J'),
+        ..Rules::STANDARD
+    };
+
+    /// Like poker, where a flush beats a straight and neither beats a full house
+    pub const POKER: Rules = Rules {
+        suited: true,
+        ranking: &[
+            HandType::HighCard,
+            HandType::OnePair,
+            HandType::TwoPairs,
+            HandType::ThreeOfAKind,
+            HandType::Straight,
+            HandType::Flush,
+            HandType::FullHouse,
+            HandType::FourOfAKind,
+            HandType::StraightFlush,
+            HandType::FiveOfAKind,
+        ],
+        ..Rules::STANDARD
+    };
+
+    fn type_rank(&self, hand_type: HandType) -> usize {
+        self.ranking
+            .iter()
+            .position(|t| *t == hand_type)
+            .unwrap_or_else(|| panic!("ranking should include {:?}", hand_type))
+    }
+
+    fn card_weight(&self, card: char) -> u8 {
+        let weight = self
+            .card_order
+            .find(card)
+            .unwrap_or_else(|| panic!("Invalid card: {:?}", card));
+        weight as u8 + 1
+    }
+}
+
+#[derive(Debug)]
+pub struct Hand {
+    pub hand_type: HandType,
+    pub cards: Vec<char>,
+    pub bid: u32,
+    rules: Rules,
+}
+
+impl Hand {
+    /// A hand without suits, so only for rules that aren't `suited`.
+    pub fn new(cards: &[char], bid: u32, rules: &Rules) -> Hand {
+        Hand::with_suits(cards, &[], bid, rules)
+    }
+
+    /// Suits only matter for straights and flushes, so they aren't kept.
+    pub fn with_suits(cards: &[char], suits: &[char], bid: u32, rules: &Rules) -> Hand {
+        assert!(
+            !rules.suited || suits.len() == cards.len(),
+            "expected a suit for every card, got {} cards and {} suits",
+            cards.len(),
+            suits.len()
+        );
+        let mut hand_type = Hand::hand_type(cards, rules);
+        if rules.suited {
+            let straight = Hand::is_straight(cards, rules);
+            let flush = Hand::is_flush(cards, suits, rules);
+            let candidates = [
+                (straight, HandType::Straight),
+                (flush, HandType::Flush),
+                (straight && flush, HandType::StraightFlush),
+            ];
+            for (_, candidate) in candidates.iter().filter(|(applies, _)| *applies) {
+                if rules.type_rank(*candidate) > rules.type_rank(hand_type) {
+                    hand_type = *candidate;
+                }
+            }
+        }
+        Hand {
+            cards: cards.to_vec(),
+            bid,
+            hand_type,
+            rules: *rules,
+        }
+    }
+
+    pub fn parse(s: &str, rules: &Rules) -> Result<Hand, ParseError> {
+        let (cards, bid) = s.split_once(' ').ok_or(ParseError::MissingBid)?;
+        let bid = bid
+            .parse()
+            .map_err(|_| ParseError::BadBid(bid.to_string()))?;
+        let chars = cards.chars().collect::<Vec<_>>();
+        let (cards, suits) = if rules.suited {
+            if chars.len() % 2 != 0 {
+                return Err(ParseError::MissingSuit);
+            }
+            let cards = chars.iter().step_by(2).copied().collect::<Vec<_>>();
+            let suits = chars.iter().skip(1).step_by(2).copied().collect::<Vec<_>>();
+            (cards, suits)
+        } else {
+            (chars, vec![])
+        };
+        if let Some(card) = cards.iter().find(|card| !rules.card_order.contains(**card)) {
+            return Err(ParseError::UnknownCard(*card));
+        }
+        if rules.suited {
+            Ok(Hand::with_suits(&cards, &suits, bid, rules))
+        } else {
+            Ok(Hand::new(&cards, bid, rules))
+        }
+    }
+
+    /// The best type the cards can make, for any number of cards. Wild cards always do best
+    /// joining whichever card there's already the most of.
+    fn hand_type(cards: &[char], rules: &Rules) -> HandType {
+        let mut card_counts = HashMap::new();
+        let mut num_wild = 0;
+        for card in cards.iter() {
+            if Some(*card) == rules.wild {
+                num_wild += 1;
+            } else {
+                let count = card_counts.entry(card).or_insert(0);
+                *count += 1;
+            }
+        }
+        let mut same_counts = card_counts.into_values().collect::<Vec<_>>();
+        same_counts.sort_unstable_by(|a, b| b.cmp(a));
+        match same_counts.first_mut() {
+            Some(most) => *most += num_wild,
+            None => same_counts.push(num_wild),
+        }
+        HandType::from_counts(&same_counts)
+    }
+
+    /// Five or more cards in a row, going by the card order, with wild cards filling any gaps.
+    /// The strongest card can also play low, before the weakest, as the ace does in A2345.
+    fn is_straight(cards: &[char], rules: &Rules) -> bool {
+        Hand::straight_weights(cards, rules).is_some()
+    }
+
+    /// The weights of the tame cards, lowest first, if they can make a straight. An ace playing
+    /// low gets a weight of 0.
+    fn straight_weights(cards: &[char], rules: &Rules) -> Option<Vec<usize>> {
+        if cards.len() < 5 || cards.len() > rules.card_order.len() {
+            return None;
+        }
+        let mut weights = cards
+            .iter()
+            .filter(|card| Some(**card) != rules.wild)
+            .map(|card| rules.card_weight(*card) as usize)
+            .collect::<Vec<_>>();
+        weights.sort_unstable();
+        if weights.windows(2).any(|pair| pair[0] == pair[1]) {
+            return None;
+        }
+        let fits = |weights: &[usize]| match (weights.first(), weights.last()) {
+            (Some(lowest), Some(highest)) => highest - lowest < cards.len(),
+            _ => true,
+        };
+        if fits(&weights) {
+            return Some(weights);
+        }
+        if weights.last() == Some(&rules.card_order.len()) {
+            weights.pop();
+            weights.insert(0, 0);
+            if fits(&weights) {
+                return Some(weights);
+            }
+        }
+        None
+    }
+
+    /// Five or more cards of the same suit, with wild cards matching any suit.
+    fn is_flush(cards: &[char], suits: &[char], rules: &Rules) -> bool {
+        let mut tame_suits = cards
+            .iter()
+            .zip(suits.iter())
+            .filter(|(card, _)| Some(**card) != rules.wild)
+            .map(|(_, suit)| suit);
+        let first = tame_suits.next();
+        cards.len() >= 5 && suits.len() == cards.len() && tame_suits.all(|suit| Some(suit) == first)
+    }
+
+    /// What the wild cards in the hand were treated as to get its type, one for each.
+    pub fn wild_replacements(&self) -> Vec<char> {
+        let rules = &self.rules;
+        let num_wild = self
+            .cards
+            .iter()
+            .filter(|card| Some(**card) == rules.wild)
+            .count();
+        let tame_cards = self
+            .cards
+            .iter()
+            .filter(|card| Some(**card) != rules.wild)
+            .copied()
+            .collect::<Vec<_>>();
+        let all_cards = rules.card_order.chars().collect::<Vec<_>>();
+
+        if matches!(self.hand_type, HandType::Straight | HandType::StraightFlush) {
+            // fill the gaps in the run, then carry on past either end of it
+            let weights = Hand::straight_weights(&self.cards, rules)
+                .expect("a straight should have weights that make one");
+            let lowest = weights.first().copied().unwrap_or(all_cards.len());
+            let lowest = lowest.min(all_cards.len() + 1 - self.cards.len());
+            (lowest..lowest + self.cards.len())
+                .filter(|weight| !weights.contains(weight))
+                // 0 is the ace playing low
+                .map(|weight| all_cards[(weight + all_cards.len() - 1) % all_cards.len()])
+                .collect()
+        } else {
+            // join whichever card there's the most of, the strongest one if that's a tie
+            let mut card_counts = HashMap::new();
+            for card in tame_cards.iter() {
+                let count = card_counts.entry(*card).or_insert(0);
+                *count += 1;
+            }
+            let most = card_counts
+                .into_iter()
+                .max_by_key(|(card, count)| (*count, rules.card_weight(*card)))
+                .map(|(card, _)| card)
+                .unwrap_or(*all_cards.last().unwrap());
+            vec![most; num_wild]
+        }
+    }
+}
+
+impl PartialEq for Hand {
+    fn eq(&self, other: &Self) -> bool {
+        self.hand_type == other.hand_type && self.cards == other.cards
+    }
+}
+
+impl Eq for Hand {}
+
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Hand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.hand_type == other.hand_type {
+            let self_cards = self.cards.iter().map(|c| self.rules.card_weight(*c));
+            let other_cards = other.cards.iter().map(|c| other.rules.card_weight(*c));
+            self_cards.cmp(other_cards)
+        } else {
+            let self_rank = self.rules.type_rank(self.hand_type);
+            let other_rank = other.rules.type_rank(other.hand_type);
+            self_rank.cmp(&other_rank)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    MissingBid,
+    BadBid(String),
+    MissingSuit,
+    UnknownCard(char),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingBid => write!(f, "expected `<cards> <bid>`"),
+            ParseError::BadBid(bid) => write!(f, "{:?} isn't a positive integer bid", bid),
+            ParseError::MissingSuit => write!(f, "expected every card to have a suit, like `Ah`"),
+            ParseError::UnknownCard(card) => write!(f, "{:?} isn't a card", card),
+        }
+    }
+}
+
+/// Ordered weakest to strongest, as in poker with wild cards. `Rules::ranking` decides how they
+/// actually rank.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPairs,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+    FiveOfAKind,
+}
+
+impl HandType {
+    /// `same_counts` is how many there are of each card, biggest first.
+    fn from_counts(same_counts: &[usize]) -> HandType {
+        let most = same_counts.first().copied().unwrap_or(0);
+        let next_most = same_counts.get(1).copied().unwrap_or(0);
+        match (most, next_most) {
+            (5.., _) => HandType::FiveOfAKind,
+            (4, _) => HandType::FourOfAKind,
+            (3, 2..) => HandType::FullHouse,
+            (3, _) => HandType::ThreeOfAKind,
+            (2, 2..) => HandType::TwoPairs,
+            (2, _) => HandType::OnePair,
+            _ => HandType::HighCard,
+        }
+    }
+}
+
+#[test]
+fn test_hand_type() {
+    let rules = Rules::STANDARD;
+    let five_of_a_kind = Hand::new(&['A', 'A', 'A', 'A', 'A'], 0, &rules);
+    let four_of_a_kind = Hand::new(&['A', 'A', 'K', 'A', 'A'], 0, &rules);
+    let full_house = Hand::new(&['A', 'A', 'K', 'K', 'K'], 0, &rules);
+    let three_of_a_kind = Hand::new(&['A', 'A', 'K', 'A', 'Q'], 0, &rules);
+    let two_pairs = Hand::new(&['A', 'K', 'K', 'Q', 'A'], 0, &rules);
+    let one_pair = Hand::new(&['J', 'A', 'K', 'Q', 'J'], 0, &rules);
+    let high_card = Hand::new(&['2', '3', '6', 'J', 'T'], 0, &rules);
+    assert_eq!(HandType::FiveOfAKind, five_of_a_kind.hand_type);
+    assert_eq!(HandType::FourOfAKind, four_of_a_kind.hand_type);
+    assert_eq!(HandType::FullHouse, full_house.hand_type);
+    assert_eq!(HandType::ThreeOfAKind, three_of_a_kind.hand_type);
+    assert_eq!(HandType::TwoPairs, two_pairs.hand_type);
+    assert_eq!(HandType::OnePair, one_pair.hand_type);
+    assert_eq!(HandType::HighCard, high_card.hand_type);
+
+    assert!(HandType::FiveOfAKind > HandType::FourOfAKind);
+
+    assert!(five_of_a_kind > four_of_a_kind);
+    assert!(four_of_a_kind > full_house);
+    assert!(full_house > three_of_a_kind);
+    assert!(three_of_a_kind > two_pairs);
+    assert!(two_pairs > one_pair);
+    assert!(one_pair > high_card);
+}
+
+#[test]
+fn test_same_type_ranking() {
+    let rules = Rules::STANDARD;
+    let five_of_a_kind1 = Hand::new(&['2', '2', '2', '2', '2'], 0, &rules);
+    let five_of_a_kind2 = Hand::new(&['A', 'A', 'A', 'A', 'A'], 0, &rules);
+
+    assert_eq!(five_of_a_kind1.hand_type, HandType::FiveOfAKind);
+    assert_eq!(five_of_a_kind2.hand_type, HandType::FiveOfAKind);
+    assert!(five_of_a_kind1 < five_of_a_kind2);
+
+    let high_card1 = Hand::new(&['7', '2', '3', 'Q', 'T'], 0, &rules);
+    let high_card2 = Hand::new(&['7', '2', '7', 'Q', 'T'], 0, &rules);
+    assert_eq!(high_card1.hand_type, HandType::HighCard);
+    assert!(high_card2 > high_card1); // 7 beats 3 in position 3
+}
+
+#[test]
+fn test_jokers() {
+    let rules = Rules::JOKERS;
+    let one_pair = Hand::new(&['J', 'A', 'K', 'Q', 'J'], 0, &rules);
+    let four_of_a_kind = Hand::new(&['K', 'T', 'J', 'J', 'T'], 0, &rules);
+    assert_eq!(one_pair.hand_type, HandType::ThreeOfAKind);
+    assert_eq!(four_of_a_kind.hand_type, HandType::FourOfAKind);
+
+    // J is the weakest card when breaking ties
+    let jokers = Hand::new(&['J', 'J', 'J', 'J', 'J'], 0, &rules);
+    let twos = Hand::new(&['2', '2', '2', '2', '2'], 0, &rules);
+    assert_eq!(jokers.hand_type, HandType::FiveOfAKind);
+    assert!(jokers < twos);
+
+    assert_eq!(
+        total_winnings(include_str!("sample.txt"), &Rules::STANDARD),
+        6440
+    );
+    assert_eq!(
+        total_winnings(include_str!("sample.txt"), &Rules::JOKERS),
+        5905
+    );
+}
+
+#[test]
+fn test_poker() {
+    let rules = Rules::POKER;
+    let hand = |s: &str| Hand::parse(&format!("{} 0", s), &rules).unwrap();
+    let straight_flush = hand("9h8hThJhQh");
+    let four_of_a_kind = hand("AhAsAdAcKh");
+    let full_house = hand("2h2s3d3c3h");
+    let flush = hand("2h9h3hJhKh");
+    let straight = hand("Ts9s8h7c6d");
+    let three_of_a_kind = hand("2h2s2d3c4h");
+    assert_eq!(straight_flush.hand_type, HandType::StraightFlush);
+    assert_eq!(four_of_a_kind.hand_type, HandType::FourOfAKind);
+    assert_eq!(full_house.hand_type, HandType::FullHouse);
+    assert_eq!(flush.hand_type, HandType::Flush);
+    assert_eq!(straight.hand_type, HandType::Straight);
+    assert_eq!(three_of_a_kind.hand_type, HandType::ThreeOfAKind);
+    assert_eq!(hand("ThJhQhKhAh").hand_type, HandType::StraightFlush);
+    assert_eq!(hand("2h3h4h5h7s").hand_type, HandType::HighCard);
+    // the ace can play low, but a straight can't wrap round past it
+    assert_eq!(hand("Ah2s3d4c5h").hand_type, HandType::Straight);
+    assert_eq!(hand("5h4h3hAh2h").hand_type, HandType::StraightFlush);
+    assert_eq!(hand("QhKsAd2c3h").hand_type, HandType::HighCard);
+    // a flush needs the suits to know about
+    assert!(!Hand::is_flush(&['2', '9', '3', 'J', 'K'], &[], &rules));
+
+    assert!(straight_flush > four_of_a_kind);
+    assert!(four_of_a_kind > full_house);
+    assert!(full_house > flush);
+    assert!(flush > straight);
+    assert!(straight > three_of_a_kind);
+
+    // a different ranking, where flushes are worth more than full houses
+    let rules = Rules {
+        ranking: &[
+            HandType::HighCard,
+            HandType::OnePair,
+            HandType::TwoPairs,
+            HandType::ThreeOfAKind,
+            HandType::Straight,
+            HandType::FullHouse,
+            HandType::Flush,
+            HandType::FourOfAKind,
+            HandType::StraightFlush,
+            HandType::FiveOfAKind,
+        ],
+        ..Rules::POKER
+    };
+    let full_house = Hand::parse("2h2s3d3c3h 0", &rules).unwrap();
+    let flush = Hand::parse("2h9h3hJhKh 0", &rules).unwrap();
+    assert!(flush > full_house);
+
+    // and one with jokers, which fill gaps in straights and match any suit
+    let rules = Rules {
+        wild: Some('J'),
+        ..Rules::POKER
+    };
+    let hand = |s: &str| Hand::parse(&format!("{} 0", s), &rules).unwrap();
+    assert_eq!(hand("9s8hJcTd7h").hand_type, HandType::Straight);
+    assert_eq!(hand("9s8hJcQd7h").hand_type, HandType::OnePair);
+    assert_eq!(hand("2h9hJsJhKh").hand_type, HandType::Flush);
+    assert_eq!(hand("9h8hJsQhTh").hand_type, HandType::StraightFlush);
+    assert_eq!(hand("9h9sJsQhTh").hand_type, HandType::ThreeOfAKind);
+    assert_eq!(hand("Ah2sJc4d5h").hand_type, HandType::Straight);
+}
+
+#[test]
+#[should_panic(expected = "expected a suit for every card")]
+fn test_poker_without_suits() {
+    Hand::new(&['2', '9', '3', 'J', 'K'], 0, &Rules::POKER);
+}
+
+#[test]
+fn test_wild_replacements() {
+    let rules = Rules::JOKERS;
+    let replacements = |cards: &str| {
+        let cards = cards.chars().collect::<Vec<_>>();
+        Hand::new(&cards, 0, &rules).wild_replacements()
+    };
+    assert_eq!(replacements("32T3K"), vec![]);
+    assert_eq!(replacements("KTJJT"), vec!['T', 'T']);
+    assert_eq!(replacements("QQQJA"), vec!['Q']);
+    assert_eq!(replacements("JJJJJ"), vec!['A'; 5]);
+    // a tie goes to the stronger card
+    assert_eq!(replacements("2JK2K"), vec!['K']);
+
+    let rules = Rules {
+        wild: Some('J'),
+        ..Rules::POKER
+    };
+    let hand = |s: &str| Hand::parse(&format!("{} 0", s), &rules).unwrap();
+    assert_eq!(hand("9s8hJcTd7h").wild_replacements(), vec!['J']);
+    assert_eq!(hand("9s8hJcJd7h").wild_replacements(), vec!['T', 'J']);
+    assert_eq!(hand("AsKhJcJdTh").wild_replacements(), vec!['J', 'Q']);
+    assert_eq!(hand("Ah2sJc4dJh").wild_replacements(), vec!['3', '5']);
+}
+
+#[test]
+fn test_big_winnings_and_ties() {
+    let input = format!("23456 {}\nAAAAA {}\n", u32::MAX, u32::MAX);
+    assert_eq!(
+        total_winnings(&input, &Rules::STANDARD),
+        3 * u64::from(u32::MAX)
+    );
+
+    let input = "32T3K 765\nKK677 28\n32T3K 1\nKK677 2\nKK677 3\nQQQJA 483\n";
+    let hands = ranked_hands(input, &Rules::STANDARD);
+    assert_eq!(ties(&hands), vec![0..2, 2..5]);
+    // ties stay in input order
+    assert_eq!(hands[2].bid, 28);
+    assert_eq!(hands[4].bid, 3);
+    assert_eq!(
+        ties(&ranked_hands(include_str!("sample.txt"), &Rules::JOKERS)),
+        vec![]
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let rules = Rules::STANDARD;
+    assert_eq!(Hand::parse("32T3K", &rules), Err(ParseError::MissingBid));
+    assert_eq!(
+        Hand::parse("32T3K -1", &rules),
+        Err(ParseError::BadBid("-1".to_string()))
+    );
+    assert_eq!(
+        Hand::parse("32X3K 1", &rules),
+        Err(ParseError::UnknownCard('X'))
+    );
+    assert_eq!(
+        Hand::parse("AhAsAdAcK 1", &Rules::POKER),
+        Err(ParseError::MissingSuit)
+    );
+}
+
+#[test]
+#[should_panic(expected = "line 2: invalid hand/bid")]
+fn test_ranked_hands_bad_line() {
+    ranked_hands("32T3K 765\nT55J5\n", &Rules::STANDARD);
+}
+
+/// Every way to pick `len` cards, ignoring order since that doesn't change the type.
+#[cfg(test)]
+fn all_hands(cards: &[char], len: usize) -> Vec<Vec<char>> {
+    match (len, cards) {
+        (0, _) => vec![vec![]],
+        (_, []) => vec![],
+        (_, [first, rest @ ..]) => {
+            let mut hands = all_hands(cards, len - 1);
+            hands.iter_mut().for_each(|hand| hand.push(*first));
+            hands.extend(all_hands(rest, len));
+            hands
+        }
+    }
+}
+
+/// The type of a hand without wild cards, worked out separately from `HandType::from_counts` by
+/// asking how many different cards there are at least so many of.
+#[cfg(test)]
+fn simple_hand_type(cards: &[char]) -> HandType {
+    let card_order = Rules::STANDARD.card_order;
+    let mut counts = vec![0; card_order.len()];
+    for card in cards {
+        counts[card_order.find(*card).unwrap()] += 1;
+    }
+    let at_least = |n: usize| counts.iter().filter(|count| **count >= n).count();
+    if at_least(5) > 0 {
+        HandType::FiveOfAKind
+    } else if at_least(4) > 0 {
+        HandType::FourOfAKind
+    } else if at_least(3) > 0 && at_least(2) > 1 {
+        HandType::FullHouse
+    } else if at_least(3) > 0 {
+        HandType::ThreeOfAKind
+    } else if at_least(2) > 1 {
+        HandType::TwoPairs
+    } else if at_least(2) > 0 {
+        HandType::OnePair
+    } else {
+        HandType::HighCard
+    }
+}
+
+/// The best type from trying every card in place of each wild card.
+#[cfg(test)]
+fn brute_force_hand_type(cards: &[char], rules: &Rules) -> HandType {
+    let all_cards = Rules::STANDARD.card_order.chars().collect::<Vec<_>>();
+    let tame_cards = cards
+        .iter()
+        .filter(|card| Some(**card) != rules.wild)
+        .copied()
+        .collect::<Vec<_>>();
+    let num_wild = cards.len() - tame_cards.len();
+    all_hands(&all_cards, num_wild)
+        .into_iter()
+        .map(|mut replacements| {
+            replacements.extend_from_slice(&tame_cards);
+            simple_hand_type(&replacements)
+        })
+        .max()
+        .unwrap()
+}
+
+#[test]
+fn test_jokers_match_brute_force() {
+    let rules = Rules::JOKERS;
+    let all_cards = rules.card_order.chars().collect::<Vec<_>>();
+    for len in 0..=6 {
+        for cards in all_hands(&all_cards, len) {
+            assert_eq!(
+                Hand::hand_type(&cards, &rules),
+                brute_force_hand_type(&cards, &rules),
+                "{:?}",
+                cards
+            );
+        }
+    }
+}
+
+/// Every `<cards> <bid>` line of the input, weakest hand first.
+pub fn ranked_hands(input: &str, rules: &Rules) -> Vec<Hand> {
+    let mut hands = input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            Hand::parse(line, rules)
+                .unwrap_or_else(|err| panic!("line {}: invalid hand/bid: {}", i + 1, err))
+        })
+        .collect::<Vec<_>>();
+    // stable, so that tied hands at least keep the order they came in
+    hands.sort();
+    hands
+}
+
+/// Runs of hands that can't be told apart, by their index in `ranked`. The puzzle promises
+/// there aren't any, because which one gets which rank would be arbitrary.
+pub fn ties(ranked: &[Hand]) -> Vec<Range<usize>> {
+    let mut ties = Vec::new();
+    let mut start = 0;
+    for end in 1..=ranked.len() {
+        if end == ranked.len() || ranked[end] != ranked[start] {
+            if end - start > 1 {
+                ties.push(start..end);
+            }
+            start = end;
+        }
+    }
+    ties
+}
+
+/// Ranks every hand in the input and adds up what each one wins.
+pub fn total_winnings(input: &str, rules: &Rules) -> u64 {
+    let hands = ranked_hands(input, rules);
+    hands.iter().enumerate().fold(0_u64, |acc, (i, hand)| {
+        (i as u64 + 1)
+            .checked_mul(u64::from(hand.bid))
+            .and_then(|winnings| winnings.checked_add(acc))
+            .expect("total winnings should fit in a u64")
+    })
+}
+
+/// Warns on stderr about any hands in the input that are tied.
+pub fn print_ties(input: &str, rules: &Rules) {
+    let hands = ranked_hands(input, rules);
+    for tie in ties(&hands) {
+        let cards = hands[tie.start].cards.iter().collect::<String>();
+        eprintln!(
+            "warning: {} hands of {} are tied for ranks {} to {}",
+            tie.len(),
+            cards,
+            tie.start + 1,
+            tie.end
+        );
+    }
+}
+
+/// Prints every hand in rank order, with everything that went into where it ended up.
+pub fn print_report(input: &str, rules: &Rules) {
+    println!(
+        "{:>5}  {:<10}  {:<13}  {:<20}  {:>5}  {:>10}  wild cards",
+        "rank", "hand", "type", "card weights", "bid", "winnings"
+    );
+    for (i, hand) in ranked_hands(input, rules).iter().enumerate() {
+        let rank = i as u64 + 1;
+        let cards = hand.cards.iter().collect::<String>();
+        let weights = hand
+            .cards
+            .iter()
+            .map(|card| rules.card_weight(*card).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let wilds = match rules.wild {
+            Some(wild) => hand
+                .wild_replacements()
+                .iter()
+                .map(|replacement| format!("{} as {}", wild, replacement))
+                .collect::<Vec<_>>()
+                .join(", "),
+            None => String::new(),
+        };
+        println!(
+            "{:>5}  {:<10}  {:<13}  {:<20}  {:>5}  {:>10}  {}",
+            rank,
+            cards,
+            format!("{:?}", hand.hand_type),
+            weights,
+            hand.bid,
+            rank * u64::from(hand.bid),
+            wilds
+        );
+    }
+}
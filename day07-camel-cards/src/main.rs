@@ -1,3 +1,6 @@
+mod camel_cards;
+use camel_cards::{print_report, print_ties, total_winnings, Rules};
+
 mod part1;
 use part1::part1;
 
@@ -5,6 +8,34 @@ mod part2;
 use part2::part2;
 
 fn main() {
-    part1();
-    part2();
+    // e.g. `cargo run -- poker hands.txt` to score some other hands under one of the rule sets,
+    // and `cargo run -- report ...` to see how every hand was ranked
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let report = args.first().is_some_and(|arg| arg == "report");
+    if report {
+        args.remove(0);
+    }
+
+    if let [rules, path] = &args[..] {
+        let rules = match rules.as_str() {
+            "standard" => Rules::STANDARD,
+            "jokers" => Rules::JOKERS,
+            "poker" => Rules::POKER,
+            _ => panic!(
+                "unknown rules {:?}, expected standard, jokers or poker",
+                rules
+            ),
+        };
+        let input = std::fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("couldn't read {}: {}", path, err));
+        if report {
+            print_report(&input, &rules);
+        }
+        print_ties(&input, &rules);
+        println!("Winnings: {}", total_winnings(&input, &rules));
+        return;
+    }
+
+    part1(report);
+    part2(report);
 }
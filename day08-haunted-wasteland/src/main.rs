@@ -1,44 +1,198 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display, io::Write};
 
-fn parse_input(input: &str) -> (Vec<char>, HashMap<String, (String, String)>) {
-    let mut graph = HashMap::new();
+mod number_theory;
+use number_theory::{crt, lcm_many};
+
+/// The nodes of the wasteland, numbered in the order they're listed so that following an
+/// instruction is just an index into `children`.
+struct Network {
+    names: Vec<String>,
+    ids: HashMap<String, u32>,
+    /// `[left, right]` for each node
+    children: Vec<[u32; 2]>,
+}
+
+impl Network {
+    fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
+
+    fn step(&self, node: u32, go_right: bool) -> u32 {
+        self.children[node as usize][go_right as usize]
+    }
+
+    /// Every node whose name ends with `suffix`, e.g. the ghosts' start and end nodes.
+    fn ids_ending_with(&self, suffix: char) -> Vec<u32> {
+        (0..self.names.len() as u32)
+            .filter(|id| self.name(*id).ends_with(suffix))
+            .collect()
+    }
+
+    /// Whether each node, by id, is one the ghosts are trying to get to.
+    fn end_node_mask(&self) -> Vec<bool> {
+        let mut is_end_node = vec![false; self.names.len()];
+        for id in self.ids_ending_with('Z') {
+            is_end_node[id as usize] = true;
+        }
+        is_end_node
+    }
+
+    /// The cycle of the ghost from each start node, in the same order as `ids_ending_with('A')`.
+    fn ghost_cycles(&self, instructions: &[bool]) -> Vec<GhostCycle> {
+        let is_end_node = self.end_node_mask();
+        self.ids_ending_with('A')
+            .into_iter()
+            .map(|start| GhostCycle::find(instructions, self, &is_end_node, start))
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ParseError {
+    NoInstructions,
+    InvalidInstruction(char),
+    InvalidLine { line_num: usize, line: String },
+    DuplicateNode(String),
+    UnknownNode { node: String, parent: String },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::NoInstructions => write!(f, "the first line should list the instructions"),
+            ParseError::InvalidInstruction(c) => {
+                write!(f, "instructions should only be L or R, found {:?}", c)
+            }
+            ParseError::InvalidLine { line_num, line } => write!(
+                f,
+                "line {}: expected `<node> = (<left>, <right>)`, found {:?}",
+                line_num, line
+            ),
+            ParseError::DuplicateNode(node) => write!(f, "node {} is listed twice", node),
+            ParseError::UnknownNode { node, parent } => {
+                write!(f, "{} leads to {}, which isn't listed", parent, node)
+            }
+        }
+    }
+}
+
+/// Returns the instructions, `true` meaning go right, and the network.
+fn parse_input(input: &str) -> Result<(Vec<bool>, Network), ParseError> {
     let mut lines = input.lines();
-    let instructions = lines.next().unwrap().chars().collect();
+    let instructions = lines
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| match c {
+            'L' => Ok(false),
+            'R' => Ok(true),
+            _ => Err(ParseError::InvalidInstruction(c)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if instructions.is_empty() {
+        return Err(ParseError::NoInstructions);
+    }
     lines.next(); // blank line
-    for line in lines {
-        let mut parts = line.split(" = ");
-        let node_name = parts.next().unwrap();
-        let children = parts
-            .next()
-            .unwrap()
-            .trim_start_matches('(')
-            .trim_end_matches(')');
-        let (left, right) = children.split_once(',').unwrap();
-        graph.insert(
-            node_name.to_string(),
-            (left.trim().to_string(), right.trim().to_string()),
-        );
+
+    // number the nodes first, since they can lead to nodes that are listed later on
+    let mut nodes = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let invalid_line = || ParseError::InvalidLine {
+            line_num: i + 3,
+            line: line.to_string(),
+        };
+        let (node_name, children) = line.split_once(" = ").ok_or_else(invalid_line)?;
+        let (left, right) = children
+            .strip_prefix('(')
+            .and_then(|children| children.strip_suffix(')'))
+            .and_then(|children| children.split_once(','))
+            .ok_or_else(invalid_line)?;
+        nodes.push((node_name.trim(), left.trim(), right.trim()));
     }
-    (instructions, graph)
+
+    let mut ids = HashMap::new();
+    for (id, (node_name, _, _)) in nodes.iter().enumerate() {
+        if ids.insert(node_name.to_string(), id as u32).is_some() {
+            return Err(ParseError::DuplicateNode(node_name.to_string()));
+        }
+    }
+    let id = |node: &str, parent: &str| {
+        ids.get(node)
+            .copied()
+            .ok_or_else(|| ParseError::UnknownNode {
+                node: node.to_string(),
+                parent: parent.to_string(),
+            })
+    };
+    let children = nodes
+        .iter()
+        .map(|(node_name, left, right)| Ok([id(left, node_name)?, id(right, node_name)?]))
+        .collect::<Result<Vec<_>, _>>()?;
+    let names = nodes
+        .iter()
+        .map(|(node_name, _, _)| node_name.to_string())
+        .collect();
+
+    Ok((
+        instructions,
+        Network {
+            names,
+            ids,
+            children,
+        },
+    ))
+}
+
+#[test]
+fn test_parse_input() {
+    let (instructions, network) = parse_input(include_str!("sample1-2.txt")).unwrap();
+    assert_eq!(instructions, vec![false, false, true]);
+    assert_eq!(network.names, vec!["AAA", "BBB", "ZZZ"]);
+    assert_eq!(network.children, vec![[1, 1], [0, 2], [2, 2]]);
+    assert_eq!(network.id("BBB"), Some(1));
+    assert_eq!(network.step(1, true), 2);
+
+    let err = parse_input("LR\n\nAAA = (BBB, ZZZ)\nZZZ = (ZZZ, ZZZ)\n").err();
+    assert_eq!(
+        err,
+        Some(ParseError::UnknownNode {
+            node: "BBB".to_string(),
+            parent: "AAA".to_string()
+        })
+    );
+    assert_eq!(parse_input("").err(), Some(ParseError::NoInstructions));
+    let err = parse_input("\n\nAAA = (AAA, AAA)\n").err();
+    assert_eq!(err, Some(ParseError::NoInstructions));
+    let err = parse_input("LXR\n\nAAA = (AAA, AAA)\n").err();
+    assert_eq!(err, Some(ParseError::InvalidInstruction('X')));
+    let err = parse_input("LR\n\nAAA = AAA, AAA\n").err();
+    assert_eq!(
+        err,
+        Some(ParseError::InvalidLine {
+            line_num: 3,
+            line: "AAA = AAA, AAA".to_string()
+        })
+    );
 }
 
 fn part1() {
     // let (input, expected_steps) = (include_str!("sample1-1.txt"), Some(2));
     // let (input, expected_steps) = (include_str!("sample1-2.txt"), Some(6));
     let (input, expected_steps) = (include_str!("my_input.txt"), Some(19637));
-    let (instructions, graph) = parse_input(input);
+    let (instructions, network) =
+        parse_input(input).unwrap_or_else(|err| panic!("couldn't read the map: {}", err));
 
+    let end_node = network.id("ZZZ").expect("should have found ZZZ");
     let mut num_steps = 0_u32;
-    let mut cur_node = &String::from("AAA");
+    let mut cur_node = network.id("AAA").expect("should have found AAA");
     let mut instructions = instructions.iter().cycle();
-    while cur_node != "ZZZ" {
+    while cur_node != end_node {
         num_steps += 1;
-        let (left, right) = graph.get(cur_node).unwrap();
-        match instructions.next().unwrap() {
-            'L' => cur_node = left,
-            'R' => cur_node = right,
-            _ => panic!("Invalid instruction"),
-        }
+        cur_node = network.step(cur_node, *instructions.next().unwrap());
     }
 
     println!("Part 1: {}", num_steps);
@@ -47,77 +201,539 @@ fn part1() {
     }
 }
 
-fn part2() {
-    // let (input, expected_steps) = (include_str!("sample2.txt"), Some(6));
-    let (input, expected_steps) = (include_str!("my_input.txt"), Some(8811050362409_u64));
-    let (instructions, graph) = parse_input(input);
+/// Every `(node, go_right)` step a ghost takes from `start`, up until it starts going round its
+/// loop again.
+fn ghost_path(instructions: &[bool], network: &Network, start: u32) -> Vec<(u32, bool)> {
+    let cycle = GhostCycle::find(instructions, network, &network.end_node_mask(), start);
+    let mut cur_node = start;
+    (0..cycle.tail_len + cycle.cycle_len)
+        .zip(instructions.iter().cycle())
+        .map(|(_, &go_right)| {
+            let step = (cur_node, go_right);
+            cur_node = network.step(cur_node, go_right);
+            step
+        })
+        .collect()
+}
 
-    let start_nodes = graph
-        .iter()
-        .filter(|(node, _)| node.ends_with('A'))
-        .map(|(node, _)| node)
-        .collect::<Vec<_>>();
+const GHOST_COLORS: [&str; 8] = [
+    "crimson",
+    "royalblue",
+    "forestgreen",
+    "darkorange",
+    "purple",
+    "deeppink",
+    "goldenrod",
+    "teal",
+];
 
-    // In part 2, we have multiple start nodes, so we need to find the number of steps for each
-    // but, the routes loop, so we need to find the LCM of the number of steps for each
-    let mut num_steps_for_start_nodes = Vec::new();
-    for start_node in start_nodes {
-        let mut num_steps = 0_u64;
-        let mut instructions = instructions.iter().cycle();
-        let mut cur_node = start_node;
-        while !cur_node.ends_with('Z') {
-            num_steps += 1;
-            let cur_instruction = instructions.next().unwrap();
+/// Renders the network as a Graphviz graph, with the edges each of `ghost_paths` walks along
+/// drawn in that ghost's colour.
+fn to_dot(network: &Network, ghost_paths: &[Vec<(u32, bool)>]) -> String {
+    // the colours of the ghosts that take each edge
+    let mut edge_colors: HashMap<(u32, bool), Vec<&str>> = HashMap::new();
+    for (path, color) in ghost_paths.iter().zip(GHOST_COLORS.iter().cycle()) {
+        for step in path {
+            let colors = edge_colors.entry(*step).or_default();
+            if !colors.contains(color) {
+                colors.push(color);
+            }
+        }
+    }
 
-            let (left, right) = graph.get(cur_node).unwrap();
-            let next_node = match cur_instruction {
-                'L' => left,
-                'R' => right,
-                _ => panic!("Invalid instruction"),
-            };
+    let mut dot = String::from("digraph wasteland {\n    node [shape=circle];\n");
+    for (id, name) in network.names.iter().enumerate() {
+        let mut attrs = Vec::new();
+        if name == "AAA" || name == "ZZZ" {
+            attrs.push("shape=doublecircle");
+        }
+        if name.ends_with('A') {
+            attrs.push("style=filled, fillcolor=palegreen");
+        } else if name.ends_with('Z') {
+            attrs.push("style=filled, fillcolor=salmon");
+        }
+        if attrs.is_empty() {
+            dot += &format!("    n{} [label=\"{}\"];\n", id, name);
+        } else {
+            dot += &format!("    n{} [label=\"{}\", {}];\n", id, name, attrs.join(", "));
+        }
+    }
 
-            // println!("{}: {:?}", num_steps, cur_nodes);
-            cur_node = next_node;
+    for (id, [left, right]) in network.children.iter().enumerate() {
+        let id = id as u32;
+        // when both ways lead to the same node, draw a single edge for both
+        let edges: &[(u32, &str, &[bool])] = if left == right {
+            &[(*left, "L/R", &[false, true])]
+        } else {
+            &[(*left, "L", &[false]), (*right, "R", &[true])]
+        };
+        for (child, label, directions) in edges {
+            let mut colors = Vec::new();
+            for go_right in directions.iter() {
+                for color in edge_colors.get(&(id, *go_right)).into_iter().flatten() {
+                    if !colors.contains(color) {
+                        colors.push(*color);
+                    }
+                }
+            }
+            if colors.is_empty() {
+                dot += &format!("    n{} -> n{} [label=\"{}\"];\n", id, child, label);
+            } else {
+                dot += &format!(
+                    "    n{} -> n{} [label=\"{}\", color=\"{}\", penwidth=2];\n",
+                    id,
+                    child,
+                    label,
+                    colors.join(":")
+                );
+            }
         }
-        num_steps_for_start_nodes.push(num_steps);
-        println!("{}: {}", start_node, num_steps);
     }
+    dot += "}\n";
+    dot
+}
 
-    let num_steps = lcm_many(&num_steps_for_start_nodes);
+#[test]
+fn test_to_dot() {
+    let (instructions, network) = parse_input(include_str!("sample1-2.txt")).unwrap();
+    assert_eq!(
+        to_dot(&network, &[]),
+        "digraph wasteland {\n    node [shape=circle];\n    \
+            n0 [label=\"AAA\", shape=doublecircle, style=filled, fillcolor=palegreen];\n    \
+            n1 [label=\"BBB\"];\n    \
+            n2 [label=\"ZZZ\", shape=doublecircle, style=filled, fillcolor=salmon];\n    \
+            n0 -> n1 [label=\"L/R\"];\n    \
+            n1 -> n0 [label=\"L\"];\n    \
+            n1 -> n2 [label=\"R\"];\n    \
+            n2 -> n2 [label=\"L/R\"];\n}\n"
+    );
 
-    println!("Part 2: {}", num_steps);
-    if let Some(expected_steps) = expected_steps {
-        assert_eq!(expected_steps, num_steps);
+    // LLR takes AAA to BBB, AAA, BBB, then LLR again to AAA, BBB, ZZZ, then ZZZ forever
+    let path = ghost_path(&instructions, &network, 0);
+    assert_eq!(
+        path,
+        vec![
+            (0, false),
+            (1, false),
+            (0, true),
+            (1, false),
+            (0, false),
+            (1, true),
+            (2, false),
+            (2, false),
+            (2, true)
+        ]
+    );
+    let dot = to_dot(&network, &[path]);
+    assert!(dot.contains("n1 -> n0 [label=\"L\", color=\"crimson\", penwidth=2];"));
+    assert!(dot.contains("n1 -> n2 [label=\"R\", color=\"crimson\", penwidth=2];"));
+}
+
+/// Prints the map as Graphviz, e.g. `cargo run -- dot ghosts | dot -Tsvg > map.svg`, optionally
+/// with the path each ghost takes coloured in.
+fn print_dot(path: Option<&str>, show_ghosts: bool) {
+    let input = match path {
+        Some(path) => std::fs::read_to_string(path)
+            .unwrap_or_else(|err| panic!("couldn't read {}: {}", path, err)),
+        None => include_str!("my_input.txt").to_string(),
+    };
+    let (instructions, network) =
+        parse_input(&input).unwrap_or_else(|err| panic!("couldn't read the map: {}", err));
+    let ghost_paths = if show_ghosts {
+        network
+            .ids_ending_with('A')
+            .into_iter()
+            .map(|start| ghost_path(&instructions, &network, start))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    print!("{}", to_dot(&network, &ghost_paths));
+}
+
+/// How one ghost's walk goes: every walk eventually repeats a (node, instruction) state, and from
+/// then on it goes round the same loop forever.
+#[derive(Debug, PartialEq, Eq)]
+struct GhostCycle {
+    /// steps taken before the walk reaches the loop
+    tail_len: u64,
+    cycle_len: u64,
+    /// steps before the loop where the ghost is on an end node, which only happen once
+    tail_ends: Vec<u64>,
+    /// steps during the first time round the loop where the ghost is on an end node, which come
+    /// around again every `cycle_len` steps
+    cycle_ends: Vec<u64>,
+}
+
+impl GhostCycle {
+    fn find(instructions: &[bool], network: &Network, is_end_node: &[bool], start: u32) -> Self {
+        // the step each (node, instruction index) state was first seen
+        let mut first_seen = vec![None; network.names.len() * instructions.len()];
+        let mut ends = Vec::new();
+        let mut cur_node = start;
+        let mut num_steps = 0_u64;
+        loop {
+            let instruction_idx = (num_steps % instructions.len() as u64) as usize;
+            let state = cur_node as usize * instructions.len() + instruction_idx;
+            if let Some(tail_len) = first_seen[state] {
+                let (tail_ends, cycle_ends) = ends.iter().partition(|&&end| end < tail_len);
+                return GhostCycle {
+                    tail_len,
+                    cycle_len: num_steps - tail_len,
+                    tail_ends,
+                    cycle_ends,
+                };
+            }
+            first_seen[state] = Some(num_steps);
+            if is_end_node[cur_node as usize] {
+                ends.push(num_steps);
+            }
+            cur_node = network.step(cur_node, instructions[instruction_idx]);
+            num_steps += 1;
+        }
+    }
+
+    fn is_end_at(&self, num_steps: u64) -> bool {
+        if num_steps < self.tail_len {
+            self.tail_ends.contains(&num_steps)
+        } else {
+            let offset = (num_steps - self.tail_len) % self.cycle_len;
+            self.cycle_ends.contains(&(self.tail_len + offset))
+        }
     }
 }
 
-fn lcm_many(nums: &[u64]) -> u64 {
-    let mut nums = nums.iter();
-    let mut x = *(nums.next().unwrap());
-    for y in nums {
-        x = lcm(x, *y);
+/// The first step where every ghost is on an end node at once, if there ever is one.
+fn first_common_end(cycles: &[GhostCycle]) -> Option<u64> {
+    // before the slowest ghost gets to its loop, it can only be at an end a few times
+    let slowest = cycles.iter().max_by_key(|cycle| cycle.tail_len)?;
+    let all_looping = slowest.tail_len;
+    if let Some(num_steps) = slowest
+        .tail_ends
+        .iter()
+        .find(|&&num_steps| cycles.iter().all(|cycle| cycle.is_end_at(num_steps)))
+    {
+        return Some(*num_steps);
+    }
+
+    // after that, each ghost is at an end exactly when the steps are one of its end offsets,
+    // modulo its cycle length, so try every combination of those
+    let mut best: Option<u64> = None;
+    let mut combinations = vec![(0_u128, 1_u128)];
+    for cycle in cycles {
+        combinations = combinations
+            .iter()
+            .flat_map(|&(residue, modulus)| {
+                cycle.cycle_ends.iter().filter_map(move |&end| {
+                    let cycle_len = u128::from(cycle.cycle_len);
+                    crt((residue, modulus), (u128::from(end) % cycle_len, cycle_len))
+                })
+            })
+            .collect();
+    }
+    for (residue, modulus) in combinations {
+        // the smallest matching step that's at least `all_looping`
+        let all_looping = u128::from(all_looping);
+        let num_steps = if residue >= all_looping {
+            residue
+        } else {
+            residue + (all_looping - residue).div_ceil(modulus) * modulus
+        };
+        if let Ok(num_steps) = u64::try_from(num_steps) {
+            best = Some(best.map_or(num_steps, |best| best.min(num_steps)));
+        }
     }
-    x
+    best
 }
 
-fn gcd(a: u64, b: u64) -> u64 {
-    let mut a = a;
-    let mut b = b;
-    while a != b {
-        if a > b {
-            a -= b;
+#[test]
+fn test_first_common_end() {
+    let (instructions, network) = parse_input(include_str!("sample2.txt")).unwrap();
+    let cycles = network.ghost_cycles(&instructions);
+    assert_eq!(
+        cycles[1],
+        GhostCycle {
+            tail_len: 1,
+            cycle_len: 6,
+            tail_ends: vec![],
+            cycle_ends: vec![3, 6],
+        }
+    );
+    assert_eq!(first_common_end(&cycles), Some(6));
+
+    // a ghost that passes an end on its way into a loop that's a different length from how
+    // long it took to get there, which breaks the LCM shortcut
+    let input = "L\n\n\
+        11A = (11Z, 11Z)\n\
+        11Z = (11B, 11B)\n\
+        11B = (11C, 11C)\n\
+        11C = (11D, 11D)\n\
+        11D = (11E, 11E)\n\
+        11E = (11B, 11B)\n\
+        22A = (22B, 22B)\n\
+        22B = (22Z, 22Z)\n\
+        22Z = (22C, 22C)\n\
+        22C = (22B, 22B)\n";
+    let (instructions, network) = parse_input(input).unwrap();
+    let cycles = network.ghost_cycles(&instructions);
+    // 11A is only ever at an end after 1 step, and 22A after 2, 5, 8, ...
+    assert_eq!(first_common_end(&cycles), None);
+    assert!(cycles[0].is_end_at(1) && !cycles[0].is_end_at(5));
+    assert!(cycles[1].is_end_at(2) && cycles[1].is_end_at(8));
+}
+
+/// How many steps the simulator walks each ghost before comparing them.
+const SIMULATION_CHUNK: usize = 1 << 20;
+
+/// Walks all the ghosts along together the slow way, as a check on the cycle arithmetic.
+struct Simulator<'a> {
+    instructions: &'a [bool],
+    network: &'a Network,
+    is_end_node: &'a [bool],
+    ghosts: Vec<u32>,
+    num_steps: u64,
+    /// walk each ghost through a chunk on its own thread
+    parallel: bool,
+}
+
+impl<'a> Simulator<'a> {
+    fn new(
+        instructions: &'a [bool],
+        network: &'a Network,
+        is_end_node: &'a [bool],
+        starts: Vec<u32>,
+        parallel: bool,
+    ) -> Self {
+        Simulator {
+            instructions,
+            network,
+            is_end_node,
+            ghosts: starts,
+            num_steps: 0,
+            parallel,
+        }
+    }
+
+    /// Moves every ghost on `len` steps, returning whether each one was on an end node at each of
+    /// those steps, starting with where it is now.
+    fn advance(&mut self, len: usize) -> Vec<Vec<bool>> {
+        use rayon::prelude::*;
+
+        let Simulator {
+            instructions,
+            network,
+            is_end_node,
+            num_steps,
+            ..
+        } = *self;
+        let walk = |node: &mut u32| {
+            let mut at_end = Vec::with_capacity(len);
+            let mut instruction_idx = (num_steps % instructions.len() as u64) as usize;
+            for _ in 0..len {
+                at_end.push(is_end_node[*node as usize]);
+                *node = network.step(*node, instructions[instruction_idx]);
+                instruction_idx = (instruction_idx + 1) % instructions.len();
+            }
+            at_end
+        };
+        let ends = if self.parallel {
+            self.ghosts.par_iter_mut().map(walk).collect()
         } else {
-            b -= a;
+            self.ghosts.iter_mut().map(walk).collect()
+        };
+        self.num_steps += len as u64;
+        ends
+    }
+
+    /// The first step where every ghost is on an end at once, giving up at `max_steps`.
+    /// `progress` is told how many steps have been checked after each chunk.
+    fn first_common_end(&mut self, max_steps: u64, mut progress: impl FnMut(u64)) -> Option<u64> {
+        while self.num_steps < max_steps {
+            let start = self.num_steps;
+            let len = (max_steps - start).min(SIMULATION_CHUNK as u64) as usize;
+            let ends = self.advance(len);
+            if let Some(offset) = (0..len).find(|&i| ends.iter().all(|at_end| at_end[i])) {
+                return Some(start + offset as u64);
+            }
+            progress(self.num_steps);
         }
+        None
+    }
+
+    /// Checks each ghost really is on an end exactly when its cycle says it should be, up to
+    /// `max_steps`. Returns the first ghost and step where it isn't.
+    fn check_cycles(
+        &mut self,
+        cycles: &[GhostCycle],
+        max_steps: u64,
+        mut progress: impl FnMut(u64),
+    ) -> Option<(usize, u64)> {
+        while self.num_steps < max_steps {
+            let start = self.num_steps;
+            let len = (max_steps - start).min(SIMULATION_CHUNK as u64) as usize;
+            let ends = self.advance(len);
+            for (ghost, (at_end, cycle)) in ends.iter().zip(cycles).enumerate() {
+                if let Some(offset) =
+                    (0..len).find(|&i| at_end[i] != cycle.is_end_at(start + i as u64))
+                {
+                    return Some((ghost, start + offset as u64));
+                }
+            }
+            progress(self.num_steps);
+        }
+        None
+    }
+}
+
+#[test]
+fn test_simulator() {
+    let (instructions, network) = parse_input(include_str!("sample2.txt")).unwrap();
+    let is_end_node = network.end_node_mask();
+    let starts = network.ids_ending_with('A');
+    let cycles = network.ghost_cycles(&instructions);
+    let cycle_lens = cycles
+        .iter()
+        .map(|cycle| cycle.cycle_len)
+        .collect::<Vec<_>>();
+    assert_eq!(lcm_many(&cycle_lens), Some(6));
+    for parallel in [false, true] {
+        let mut simulator = Simulator::new(
+            &instructions,
+            &network,
+            &is_end_node,
+            starts.clone(),
+            parallel,
+        );
+        assert_eq!(simulator.first_common_end(1000, |_| {}), Some(6));
+        let mut simulator = Simulator::new(
+            &instructions,
+            &network,
+            &is_end_node,
+            starts.clone(),
+            parallel,
+        );
+        assert_eq!(simulator.check_cycles(&cycles, 1000, |_| {}), None);
     }
-    a
+    let mut simulator = Simulator::new(&instructions, &network, &is_end_node, starts, false);
+    assert_eq!(simulator.first_common_end(6, |_| {}), None);
+
+    // the real thing is too long to walk all the way, but its cycles should hold for a while
+    let (instructions, network) = parse_input(include_str!("my_input.txt")).unwrap();
+    let is_end_node = network.end_node_mask();
+    let starts = network.ids_ending_with('A');
+    let cycles = network.ghost_cycles(&instructions);
+    let mut simulator = Simulator::new(&instructions, &network, &is_end_node, starts, true);
+    let mut num_progress_calls = 0;
+    let mismatch = simulator.check_cycles(&cycles, 3 << 20, |_| num_progress_calls += 1);
+    assert_eq!(mismatch, None);
+    assert_eq!(num_progress_calls, 3);
 }
 
-fn lcm(a: u64, b: u64) -> u64 {
-    a * b / gcd(a, b)
+/// Walks the ghosts the slow way for up to `max_steps`, checking each one's cycle as it goes, and
+/// compares where they all first meet at an end with what the cycle arithmetic says.
+fn simulate(max_steps: u64, parallel: bool) {
+    let (instructions, network) = parse_input(include_str!("my_input.txt"))
+        .unwrap_or_else(|err| panic!("couldn't read the map: {}", err));
+    let is_end_node = network.end_node_mask();
+    let starts = network.ids_ending_with('A');
+    let cycles = network.ghost_cycles(&instructions);
+    let progress = |num_steps: u64| {
+        print!("\r{} steps checked", num_steps);
+        std::io::stdout().flush().unwrap();
+    };
+
+    let mut simulator = Simulator::new(
+        &instructions,
+        &network,
+        &is_end_node,
+        starts.clone(),
+        parallel,
+    );
+    let mismatch = simulator.check_cycles(&cycles, max_steps, progress);
+    println!();
+    match mismatch {
+        Some((ghost, num_steps)) => panic!(
+            "the ghost from {} doesn't follow its cycle at step {}",
+            network.name(starts[ghost]),
+            num_steps
+        ),
+        None => println!("every ghost followed its cycle for {} steps", max_steps),
+    }
+
+    let expected = first_common_end(&cycles).filter(|&num_steps| num_steps < max_steps);
+    let mut simulator = Simulator::new(&instructions, &network, &is_end_node, starts, parallel);
+    let found = simulator.first_common_end(max_steps, progress);
+    println!();
+    match found {
+        Some(num_steps) => println!("the ghosts are all at an end after {} steps", num_steps),
+        None => println!(
+            "the ghosts aren't all at an end in the first {} steps",
+            max_steps
+        ),
+    }
+    assert_eq!(
+        found, expected,
+        "the simulation should agree with the cycles"
+    );
+}
+
+fn part2() {
+    // let (input, expected_steps) = (include_str!("sample2.txt"), Some(6));
+    let (input, expected_steps) = (include_str!("my_input.txt"), Some(8811050362409_u64));
+    let (instructions, network) =
+        parse_input(input).unwrap_or_else(|err| panic!("couldn't read the map: {}", err));
+
+    // In part 2, we have multiple start nodes, and walking them all together takes far too long.
+    // But each ghost's walk ends up in a loop, so we can work out when the loops line up.
+    let cycles = network.ghost_cycles(&instructions);
+    for (start_node, cycle) in network.ids_ending_with('A').into_iter().zip(cycles.iter()) {
+        println!(
+            "{}: loops every {} steps after {}, at an end on steps {:?} then {:?}",
+            network.name(start_node),
+            cycle.cycle_len,
+            cycle.tail_len,
+            cycle.tail_ends,
+            cycle.cycle_ends
+        );
+    }
+    let cycle_lens = cycles
+        .iter()
+        .map(|cycle| cycle.cycle_len)
+        .collect::<Vec<_>>();
+    match lcm_many(&cycle_lens) {
+        Some(period) => println!("the ghosts all repeat every {} steps", period),
+        None => println!("the ghosts take more than u64::MAX steps to all repeat"),
+    }
+
+    let num_steps = first_common_end(&cycles).expect("the ghosts should all be at an end together");
+
+    println!("Part 2: {}", num_steps);
+    if let Some(expected_steps) = expected_steps {
+        assert_eq!(expected_steps, num_steps);
+    }
 }
 
 fn main() {
-    part1();
-    part2();
+    // `cargo run -- dot [ghosts] [<file>]` draws the map instead of solving it, and
+    // `cargo run -- simulate [parallel] [<max steps>]` checks part 2 by brute force
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("dot") => {
+            let show_ghosts = args.get(1).is_some_and(|arg| arg == "ghosts");
+            let path = args.get(if show_ghosts { 2 } else { 1 });
+            print_dot(path.map(String::as_str), show_ghosts);
+        }
+        Some("simulate") => {
+            let parallel = args.get(1).is_some_and(|arg| arg == "parallel");
+            let max_steps = args
+                .get(if parallel { 2 } else { 1 })
+                .map(|arg| arg.parse().expect("should have found a number of steps"))
+                .unwrap_or(100_000_000);
+            simulate(max_steps, parallel);
+        }
+        _ => {
+            part1();
+            part2();
+        }
+    }
 }
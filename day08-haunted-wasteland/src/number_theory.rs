@@ -0,0 +1,193 @@
+use std::mem::swap;
+
+/// Binary GCD, so it's quick however lopsided the inputs are. `gcd(0, x)` is `x`.
+pub fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    if a == 0 || b == 0 {
+        return a | b;
+    }
+    let shift = (a | b).trailing_zeros();
+    a >>= a.trailing_zeros();
+    loop {
+        b >>= b.trailing_zeros();
+        if a > b {
+            swap(&mut a, &mut b);
+        }
+        b -= a;
+        if b == 0 {
+            return a << shift;
+        }
+    }
+}
+
+// not needed by this puzzle, but part of the toolbox for the others
+#[allow(dead_code)]
+pub fn gcd(a: u64, b: u64) -> u64 {
+    gcd_u128(u128::from(a), u128::from(b)) as u64
+}
+
+/// `None` if the LCM doesn't fit. `lcm(0, x)` is 0.
+pub fn checked_lcm_u128(a: u128, b: u128) -> Option<u128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd_u128(a, b)).checked_mul(b)
+}
+
+pub fn checked_lcm(a: u64, b: u64) -> Option<u64> {
+    checked_lcm_u128(u128::from(a), u128::from(b)).and_then(|lcm| u64::try_from(lcm).ok())
+}
+
+/// The LCM of all of them, which is 1 if there aren't any, or `None` if it doesn't fit.
+pub fn lcm_many(nums: &[u64]) -> Option<u64> {
+    nums.iter().try_fold(1, |acc, num| checked_lcm(acc, *num))
+}
+
+/// Returns `(g, x, y)` with `g = gcd(a, b) = a*x + b*y`. `mod_inverse` has its own copy that
+/// works mod `m`, so it can go up to `u128::MAX` rather than `i128::MAX`.
+#[allow(dead_code)]
+pub fn ext_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+fn add_mod(a: u128, b: u128, m: u128) -> u128 {
+    // both are below m, so if it overflows it's still only over by less than m
+    let (sum, overflowed) = a.overflowing_add(b);
+    if overflowed || sum >= m {
+        sum.wrapping_sub(m)
+    } else {
+        sum
+    }
+}
+
+fn sub_mod(a: u128, b: u128, m: u128) -> u128 {
+    if a >= b {
+        a - b
+    } else {
+        m - (b - a)
+    }
+}
+
+/// `a * b % m` without overflowing, for any `m` up to `u128::MAX`.
+pub fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    let (mut a, mut b) = (a % m, b % m);
+    let mut product = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            product = add_mod(product, a, m);
+        }
+        a = add_mod(a, a, m);
+        b >>= 1;
+    }
+    product
+}
+
+/// The `x` with `a * x ≡ 1 (mod m)`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: u128, m: u128) -> Option<u128> {
+    if m == 0 {
+        return None;
+    }
+    // extended Euclid, but only keeping a's coefficient, and keeping it mod m
+    let (mut old_r, mut r) = (a % m, m);
+    let (mut old_x, mut x) = (1 % m, 0);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, sub_mod(old_x, mul_mod(q, x, m), m));
+    }
+    (old_r == 1).then_some(old_x)
+}
+
+/// Combines `x ≡ a.0 (mod a.1)` and `x ≡ b.0 (mod b.1)` into a single `(residue, modulus)`.
+/// The moduli don't have to be coprime, so there might not be any such `x`.
+pub fn crt(a: (u128, u128), b: (u128, u128)) -> Option<(u128, u128)> {
+    let ((a_residue, a_modulus), (b_residue, b_modulus)) = (a, b);
+    assert!(a_modulus > 0 && b_modulus > 0, "moduli should be positive");
+    let a_residue = a_residue % a_modulus;
+
+    // need a_residue + a_modulus * k ≡ b_residue (mod b_modulus)
+    let g = gcd_u128(a_modulus, b_modulus);
+    let diff = sub_mod(b_residue % b_modulus, a_residue % b_modulus, b_modulus);
+    if !diff.is_multiple_of(g) {
+        return None;
+    }
+    let step = b_modulus / g;
+    let inv = mod_inverse(a_modulus / g, step).expect("should be coprime after dividing by gcd");
+    let k = mul_mod(diff / g, inv, step);
+
+    let modulus =
+        checked_lcm_u128(a_modulus, b_modulus).expect("combined modulus should fit in a u128");
+    // k < step, so this stays below modulus
+    Some((a_residue + a_modulus * k, modulus))
+}
+
+#[test]
+fn test_gcd_lcm() {
+    assert_eq!(gcd(0, 0), 0);
+    assert_eq!(gcd(0, 12), 12);
+    assert_eq!(gcd(12, 0), 12);
+    assert_eq!(gcd(12, 18), 6);
+    assert_eq!(gcd(1, u64::MAX), 1);
+    assert_eq!(gcd(1 << 40, 3 << 20), 1 << 20);
+    assert_eq!(gcd_u128(u128::MAX, u128::MAX / 3), u128::MAX / 3);
+
+    assert_eq!(checked_lcm(4, 6), Some(12));
+    assert_eq!(checked_lcm(0, 6), Some(0));
+    assert_eq!(checked_lcm(u64::MAX, u64::MAX), Some(u64::MAX));
+    assert_eq!(checked_lcm(u64::MAX, u64::MAX - 1), None);
+    assert_eq!(
+        checked_lcm_u128(u128::from(u64::MAX), u128::from(u64::MAX - 1)),
+        Some(u128::from(u64::MAX) * u128::from(u64::MAX - 1))
+    );
+    assert_eq!(lcm_many(&[]), Some(1));
+    assert_eq!(lcm_many(&[2, 3, 4]), Some(12));
+    assert_eq!(lcm_many(&[1 << 40, 3 << 40, 5 << 40]), Some(15 << 40));
+    assert_eq!(lcm_many(&[u64::MAX, 2]), None);
+}
+
+#[test]
+fn test_ext_gcd_and_inverse() {
+    for (a, b) in [(240, 46), (46, 240), (-240, 46), (0, 7), (7, 0), (17, 17)] {
+        let (g, x, y) = ext_gcd(a, b);
+        assert_eq!(
+            g,
+            gcd(a.unsigned_abs() as u64, b.unsigned_abs() as u64) as i128
+        );
+        assert_eq!(a * x + b * y, g, "{} {}", a, b);
+    }
+
+    assert_eq!(mod_inverse(3, 7), Some(5));
+    assert_eq!(mod_inverse(4, 8), None);
+    assert_eq!(mod_inverse(5, 1), Some(0));
+    let m = u128::MAX - 158; // the largest prime below 2^128
+    let inv = mod_inverse(123_456_789, m).unwrap();
+    assert_eq!(mul_mod(123_456_789, inv, m), 1);
+}
+
+#[test]
+fn test_crt() {
+    assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+    // non-coprime moduli
+    assert_eq!(crt((2, 6), (8, 10)), Some((8, 30)));
+    assert_eq!(crt((1, 6), (2, 4)), None);
+    assert_eq!(crt((0, 1), (4, 7)), Some((4, 7)));
+
+    let a = (5, u128::from(u64::MAX));
+    let b = (7, u128::from(u64::MAX - 1));
+    let (residue, modulus) = crt(a, b).unwrap();
+    assert_eq!(residue % a.1, 5);
+    assert_eq!(residue % b.1, 7);
+    assert_eq!(modulus, a.1 * b.1);
+}
@@ -1,44 +1,486 @@
-fn parse_line(line: &str) -> Vec<i64> {
-    line.split_whitespace()
-        .map(|s| s.parse::<i64>().unwrap())
+use std::fmt::Display;
+
+mod polynomial;
+use polynomial::Polynomial;
+
+#[derive(Debug, PartialEq, Eq)]
+struct ParseError {
+    line_num: usize,
+    word: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}: {:?} isn't a whole number",
+            self.line_num, self.word
+        )
+    }
+}
+
+/// Reads every history as i128, so readings too big for an i64 still fit.
+fn parse_input(input: &str) -> Result<Vec<Vec<i128>>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            line.split_whitespace()
+                .map(|word| {
+                    word.parse().map_err(|_| ParseError {
+                        line_num: i + 1,
+                        word: word.to_string(),
+                    })
+                })
+                .collect()
+        })
         .collect()
 }
 
-fn sequence(history: &[i64]) -> Vec<Vec<i64>> {
+#[test]
+fn test_parse_input() {
+    let histories = parse_input("0 3 -6\n\n170141183460469231731687303715884105727\n").unwrap();
+    assert_eq!(histories, vec![vec![0, 3, -6], vec![], vec![i128::MAX]]);
+    assert_eq!(
+        parse_input("1 2 3\n4 5.5 6\n"),
+        Err(ParseError {
+            line_num: 2,
+            word: "5.5".to_string()
+        })
+    );
+}
+
+/// The integer types a pyramid can be built out of.
+trait Reading: Copy + Default + Display + PartialEq {
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+}
+
+impl Reading for i64 {
+    fn checked_add(self, other: Self) -> Option<Self> {
+        i64::checked_add(self, other)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        i64::checked_sub(self, other)
+    }
+}
+
+impl Reading for i128 {
+    fn checked_add(self, other: Self) -> Option<Self> {
+        i128::checked_add(self, other)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        i128::checked_sub(self, other)
+    }
+}
+
+fn sequence<T: Reading>(history: &[T]) -> Result<Vec<Vec<T>>, ExtrapolateError> {
+    if history.is_empty() {
+        return Err(ExtrapolateError::Empty);
+    }
     let mut seqs = vec![history.to_vec()];
-    let mut all_zero = history.iter().all(|x| *x == 0);
     let mut prev_history = &seqs[0];
-    while !all_zero {
+    while prev_history.iter().any(|x| *x != T::default()) {
+        if prev_history.len() == 1 {
+            return Err(ExtrapolateError::NeverSettles);
+        }
         let deltas = prev_history
             .windows(2)
-            .map(|w| w[1] - w[0])
-            .collect::<Vec<_>>();
-        all_zero = deltas.iter().all(|x| *x == 0);
+            .map(|w| w[1].checked_sub(w[0]).ok_or(ExtrapolateError::Overflow))
+            .collect::<Result<Vec<_>, _>>()?;
         seqs.push(deltas);
         prev_history = seqs.last().unwrap();
     }
-    seqs
+    Ok(seqs)
+}
+
+fn extrapolate_next<T: Reading>(sequence: &[Vec<T>]) -> Result<T, ExtrapolateError> {
+    let mut last_elems = sequence.iter().rev().map(|xs| *xs.last().unwrap());
+    last_elems
+        .try_fold(T::default(), |acc, elem| acc.checked_add(elem))
+        .ok_or(ExtrapolateError::Overflow)
+}
+
+fn extrapolate_prev<T: Reading>(sequence: &[Vec<T>]) -> Result<T, ExtrapolateError> {
+    let mut first_elems = sequence.iter().rev().map(|xs| *xs.first().unwrap());
+    first_elems
+        .try_fold(T::default(), |acc, elem| elem.checked_sub(acc))
+        .ok_or(ExtrapolateError::Overflow)
+}
+
+/// The pyramid of differences, built out of i64 unless the numbers get too big for one.
+#[derive(Debug, PartialEq, Eq)]
+enum Pyramid {
+    Narrow(Vec<Vec<i64>>),
+    Wide(Vec<Vec<i128>>),
+}
+
+impl Pyramid {
+    fn new(history: &[i128]) -> Result<Self, ExtrapolateError> {
+        // the values on either side have to fit too
+        fn build<T: Reading>(history: &[T]) -> Result<Vec<Vec<T>>, ExtrapolateError> {
+            let seq = sequence(history)?;
+            extrapolate_next(&seq)?;
+            extrapolate_prev(&seq)?;
+            Ok(seq)
+        }
+
+        let narrow = history
+            .iter()
+            .map(|x| i64::try_from(*x))
+            .collect::<Result<Vec<_>, _>>();
+        if let Ok(narrow) = narrow {
+            match build(&narrow) {
+                Ok(seq) => return Ok(Pyramid::Narrow(seq)),
+                Err(ExtrapolateError::Overflow) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        build(history).map(Pyramid::Wide)
+    }
+
+    fn next(&self) -> i128 {
+        match self {
+            Pyramid::Narrow(seq) => extrapolate_next(seq).map(i128::from),
+            Pyramid::Wide(seq) => extrapolate_next(seq),
+        }
+        .expect("should fit, since new checked it")
+    }
+
+    fn prev(&self) -> i128 {
+        match self {
+            Pyramid::Narrow(seq) => extrapolate_prev(seq).map(i128::from),
+            Pyramid::Wide(seq) => extrapolate_prev(seq),
+        }
+        .expect("should fit, since new checked it")
+    }
+
+    /// The first number on each row, down to the last one that isn't all zeros.
+    fn leading_diffs(&self) -> Vec<i128> {
+        fn leading<T: Copy + Into<i128>>(seq: &[Vec<T>]) -> Vec<i128> {
+            // the last row is the one that's all zeros
+            seq[..seq.len() - 1]
+                .iter()
+                .map(|row| row[0].into())
+                .collect()
+        }
+
+        match self {
+            Pyramid::Narrow(seq) => leading(seq),
+            Pyramid::Wide(seq) => leading(seq),
+        }
+    }
+
+    fn explain(&self) -> String {
+        match self {
+            Pyramid::Narrow(seq) => explain(seq),
+            Pyramid::Wide(seq) => explain(seq),
+        }
+    }
+}
+
+#[test]
+fn test_pyramid() {
+    let pyramid = Pyramid::new(&[10, 13, 16, 21, 30, 45]).unwrap();
+    assert!(matches!(pyramid, Pyramid::Narrow(_)));
+    assert_eq!((pyramid.prev(), pyramid.next()), (5, 68));
+
+    // the differences overflow an i64
+    let (min, max) = (i128::from(i64::MIN), i128::from(i64::MAX));
+    assert_eq!(
+        sequence(&[i64::MIN, i64::MAX]),
+        Err(ExtrapolateError::Overflow)
+    );
+    let pyramid = Pyramid::new(&[min, max, min, max, min]).unwrap_err();
+    assert_eq!(pyramid, ExtrapolateError::NeverSettles);
+    let pyramid = Pyramid::new(&[min, 0, -min]).unwrap();
+    assert!(matches!(pyramid, Pyramid::Wide(_)));
+    assert_eq!((pyramid.prev(), pyramid.next()), (2 * min, -2 * min));
+    // only the values on either side do
+    let pyramid = Pyramid::new(&[max, 0, -max]).unwrap();
+    assert!(matches!(pyramid, Pyramid::Wide(_)));
+    assert_eq!((pyramid.prev(), pyramid.next()), (2 * max, -2 * max));
+    let pyramid = Pyramid::new(&[-(1 << 62), 0, 1 << 62]).unwrap();
+    assert!(matches!(pyramid, Pyramid::Wide(_)));
+    assert_eq!(pyramid.next(), 1 << 63);
+    // a reading doesn't even fit
+    let pyramid = Pyramid::new(&[max + 1, max + 1]).unwrap();
+    assert_eq!(pyramid.next(), max + 1);
+
+    assert_eq!(
+        Pyramid::new(&[i128::MIN, i128::MAX]),
+        Err(ExtrapolateError::Overflow)
+    );
+    assert_eq!(Pyramid::new(&[]), Err(ExtrapolateError::Empty));
+    assert_eq!(
+        Pyramid::new(&[1, 2, 4]),
+        Err(ExtrapolateError::NeverSettles)
+    );
+}
+
+/// Draws the pyramid of differences like the puzzle does, with each row's previous value on the
+/// left and next value on the right.
+fn explain<T: Reading>(sequence: &[Vec<T>]) -> String {
+    let rows = (0..sequence.len())
+        .map(|row| {
+            // the rows below this one are the pyramid for extrapolating this one, and they can't
+            // overflow if the whole thing doesn't
+            let prev = extrapolate_prev(&sequence[row..]).expect("should fit");
+            let next = extrapolate_next(&sequence[row..]).expect("should fit");
+            let mut row = sequence[row].clone();
+            row.insert(0, prev);
+            row.push(next);
+            row
+        })
+        .collect::<Vec<_>>();
+
+    // every number gets the same width, so each row can sit half a number further in
+    let widest = rows
+        .iter()
+        .flatten()
+        .map(|x| x.to_string().len())
+        .max()
+        .unwrap_or_default();
+    let width = (widest + 2).next_multiple_of(2);
+    let mut text = String::new();
+    for (i, row) in rows.iter().enumerate() {
+        text += &" ".repeat(i * width / 2);
+        for x in row {
+            text += &format!("{:>width$}", x, width = width);
+        }
+        text += "\n";
+    }
+    text
+}
+
+#[test]
+fn test_explain() {
+    let histories = parse_input(include_str!("sample.txt")).unwrap();
+    assert_eq!(
+        Pyramid::new(&histories[2]).unwrap().explain(),
+        concat!(
+            "   5  10  13  16  21  30  45  68\n",
+            "     5   3   3   5   9  15  23\n",
+            "      -2   0   2   4   6   8\n",
+            "         2   2   2   2   2\n",
+            "           0   0   0   0\n",
+        )
+    );
+}
+
+/// Prints the pyramid for every history, to show how each one is extrapolated.
+fn print_explanations(input: &str) {
+    let histories =
+        parse_input(input).unwrap_or_else(|err| panic!("couldn't read the histories: {}", err));
+    for (i, history) in histories.iter().enumerate() {
+        match Pyramid::new(history) {
+            Ok(pyramid) => println!(
+                "line {}: {} comes before and {} comes after\n{}",
+                i + 1,
+                pyramid.prev(),
+                pyramid.next(),
+                pyramid.explain()
+            ),
+            Err(err) => println!("line {}: {}\n", i + 1, err),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ExtrapolateError {
+    Empty,
+    /// the differences got down to a single reading without ever all being zero
+    NeverSettles,
+    Overflow,
+}
+
+impl Display for ExtrapolateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtrapolateError::Empty => write!(f, "the history is empty"),
+            ExtrapolateError::NeverSettles => {
+                write!(f, "the differences never get down to all zeros")
+            }
+            ExtrapolateError::Overflow => write!(f, "the numbers got too big for an i128"),
+        }
+    }
+}
+
+/// A history in Newton's forward difference form: the first number on each row of the pyramid,
+/// down to the last row that isn't all zeros. That's enough to work out any reading at all
+/// without building the rest of the pyramid.
+#[derive(Debug, PartialEq, Eq)]
+struct NewtonForm {
+    leading_diffs: Vec<i128>,
+    len: usize,
+}
+
+impl NewtonForm {
+    fn new(history: &[i128]) -> Result<Self, ExtrapolateError> {
+        // the pyramid only goes up to i128 if it has to
+        let pyramid = Pyramid::new(history)?;
+        Ok(NewtonForm {
+            leading_diffs: pyramid.leading_diffs(),
+            len: history.len(),
+        })
+    }
+
+    /// The reading at `index` in the history, which can be off either end of it.
+    fn value_at(&self, index: i128) -> Result<i128, ExtrapolateError> {
+        // sum of binomial(index, j) * leading_diffs[j], which works for negative indexes too
+        let mut value = 0_i128;
+        let mut binomial = 1_i128;
+        for (j, diff) in self.leading_diffs.iter().enumerate() {
+            if j > 0 {
+                let j = j as i128;
+                // binomial(index, j - 1) * (index - j + 1) is always a multiple of j
+                binomial = (index - j + 1)
+                    .checked_mul(binomial)
+                    .ok_or(ExtrapolateError::Overflow)?
+                    / j;
+            }
+            value = binomial
+                .checked_mul(*diff)
+                .and_then(|term| value.checked_add(term))
+                .ok_or(ExtrapolateError::Overflow)?;
+        }
+        Ok(value)
+    }
+
+    /// The reading `steps` after the last one.
+    fn forward(&self, steps: u64) -> Result<i128, ExtrapolateError> {
+        self.value_at(self.len as i128 - 1 + i128::from(steps))
+    }
+
+    /// The reading `steps` before the first one.
+    fn backward(&self, steps: u64) -> Result<i128, ExtrapolateError> {
+        self.value_at(-i128::from(steps))
+    }
 }
 
-fn extrapolate_next(sequence: &[Vec<i64>]) -> i64 {
-    let last_elems = sequence.iter().rev().map(|xs| *xs.last().unwrap());
-    last_elems.sum()
+#[test]
+fn test_newton_form() {
+    let histories = parse_input(include_str!("sample.txt")).unwrap();
+    let newton = NewtonForm::new(&histories[2]).unwrap();
+    assert_eq!(newton.leading_diffs, vec![10, 3, 0, 2]);
+    assert_eq!(newton.forward(1), Ok(68));
+    assert_eq!(newton.backward(1), Ok(5));
+    // the readings themselves come back out
+    for (i, x) in histories[2].iter().enumerate() {
+        assert_eq!(newton.value_at(i as i128), Ok(*x));
+    }
+
+    // agrees with building the pyramid a step at a time
+    for history in histories.iter() {
+        let mut history = history.clone();
+        let newton = NewtonForm::new(&history).unwrap();
+        for steps in 1..10 {
+            let next = extrapolate_next(&sequence(&history).unwrap()).unwrap();
+            assert_eq!(newton.forward(steps), Ok(next));
+            history.push(next);
+        }
+        for steps in 1..10 {
+            let prev = extrapolate_prev(&sequence(&history).unwrap()).unwrap();
+            assert_eq!(newton.backward(steps), Ok(prev));
+            history.insert(0, prev);
+        }
+    }
+
+    assert_eq!(NewtonForm::new(&[]), Err(ExtrapolateError::Empty));
+    assert_eq!(
+        NewtonForm::new(&[1, 2, 4]),
+        Err(ExtrapolateError::NeverSettles)
+    );
+    assert_eq!(NewtonForm::new(&[7]), Err(ExtrapolateError::NeverSettles));
+    assert_eq!(NewtonForm::new(&[0]).unwrap().forward(5), Ok(0));
+    // one whose differences only fit in an i128
+    let min = i128::from(i64::MIN);
+    let wide = NewtonForm::new(&[min, 0, -min]).unwrap();
+    assert_eq!(wide.leading_diffs, vec![min, -min]);
+    assert_eq!(wide.forward(1), Ok(-2 * min));
+    let constant = NewtonForm::new(&[7, 7]).unwrap();
+    assert_eq!(constant.forward(u64::MAX), Ok(7));
+    let cubic = NewtonForm::new(&[0, 1, 8, 27, 64]).unwrap();
+    assert_eq!(cubic.backward(3), Ok(-27));
+    assert_eq!(cubic.forward(1_000_000 - 4), Ok(1_000_000_i128.pow(3)));
+    assert_eq!(cubic.forward(u64::MAX), Err(ExtrapolateError::Overflow));
 }
 
-fn extrapolate_prev(sequence: &[Vec<i64>]) -> i64 {
-    let first_elems = sequence.iter().rev().map(|xs| *xs.first().unwrap());
-    first_elems.fold(0, |acc, elem| elem - acc)
+/// The sum of the readings `steps` past the end of every history, or before the start of every
+/// history if `steps` is negative.
+fn sum_predictions(input: &str, steps: i64) -> i128 {
+    let histories =
+        parse_input(input).unwrap_or_else(|err| panic!("couldn't read the histories: {}", err));
+    histories
+        .iter()
+        .enumerate()
+        .map(|(i, history)| {
+            let newton =
+                NewtonForm::new(history).unwrap_or_else(|err| panic!("line {}: {}", i + 1, err));
+            let prediction = if steps < 0 {
+                newton.backward(steps.unsigned_abs())
+            } else {
+                newton.forward(steps as u64)
+            };
+            prediction.unwrap_or_else(|err| panic!("line {}: {}", i + 1, err))
+        })
+        .try_fold(0_i128, i128::checked_add)
+        .expect("the sum should fit in an i128")
+}
+
+impl NewtonForm {
+    /// The polynomial that generates the readings, with `x` the reading's index.
+    fn polynomial(&self) -> Result<Polynomial, ExtrapolateError> {
+        Polynomial::from_newton(&self.leading_diffs).ok_or(ExtrapolateError::Overflow)
+    }
+}
+
+#[test]
+fn test_polynomial() {
+    let histories = parse_input(include_str!("my_input.txt")).unwrap();
+    for history in histories.iter().take(20) {
+        let poly = NewtonForm::new(history).unwrap().polynomial().unwrap();
+        for (i, x) in history.iter().enumerate() {
+            let value = poly.eval(i as i128);
+            assert!(value.is_integer());
+            assert_eq!(value.numer, *x);
+        }
+    }
+}
+
+/// Prints the polynomial behind every history, so it's clear why they extrapolate how they do.
+fn print_polynomials(input: &str) {
+    let histories =
+        parse_input(input).unwrap_or_else(|err| panic!("couldn't read the histories: {}", err));
+    for (i, history) in histories.iter().enumerate() {
+        let poly = NewtonForm::new(history).and_then(|newton| newton.polynomial());
+        match poly {
+            Ok(poly) => {
+                // the first row of the pyramid is the history itself
+                let (degree, zero_row) = match poly.degree() {
+                    Some(degree) => (degree.to_string(), degree + 2),
+                    None => ("-".to_string(), 1),
+                };
+                println!(
+                    "line {}: {} (degree {}, all zeros on row {})",
+                    i + 1,
+                    poly,
+                    degree,
+                    zero_row
+                );
+            }
+            Err(err) => println!("line {}: {}", i + 1, err),
+        }
+    }
 }
 
 fn part1() {
     // let (input, expected_sum) = (include_str!("sample.txt"), Some(114));
     let (input, expected_sum) = (include_str!("my_input.txt"), Some(1842168671));
 
-    let history_lines = input.lines().map(parse_line);
-    let sum = history_lines
-        .map(|history| sequence(&history))
-        .map(|seq| extrapolate_next(&seq))
-        .sum::<i64>();
+    let sum = sum_predictions(input, 1);
     println!("Part 1: {}", sum);
     if let Some(expected_sum) = expected_sum {
         assert_eq!(expected_sum, sum);
@@ -49,11 +491,7 @@ fn part2() {
     // let (input, expected_sum) = (include_str!("sample.txt"), Some(2));
     let (input, expected_sum) = (include_str!("my_input.txt"), Some(903));
 
-    let history_lines = input.lines().map(parse_line);
-    let sum = history_lines
-        .map(|history| sequence(&history))
-        .map(|seq| extrapolate_prev(&seq))
-        .sum::<i64>();
+    let sum = sum_predictions(input, -1);
     println!("Part 2: {}", sum);
     if let Some(expected_sum) = expected_sum {
         assert_eq!(expected_sum, sum);
@@ -61,6 +499,27 @@ fn part2() {
 }
 
 fn main() {
-    part1();
-    part2();
+    // `cargo run -- predict <steps>` sums the readings any number of steps ahead, or behind if
+    // it's negative, `cargo run -- fit` prints the polynomial behind each history, and
+    // `cargo run -- --explain [<file>]` draws the pyramids
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    match args[..] {
+        ["fit"] => print_polynomials(include_str!("my_input.txt")),
+        ["predict", steps] => {
+            let steps = steps.parse().expect("should have found a number of steps");
+            let sum = sum_predictions(include_str!("my_input.txt"), steps);
+            println!("Sum of predictions {} steps away: {}", steps, sum);
+        }
+        ["--explain"] => print_explanations(include_str!("my_input.txt")),
+        ["--explain", path] => {
+            let input = std::fs::read_to_string(path)
+                .unwrap_or_else(|err| panic!("couldn't read {}: {}", path, err));
+            print_explanations(&input);
+        }
+        _ => {
+            part1();
+            part2();
+        }
+    }
 }
@@ -0,0 +1,168 @@
+use std::fmt::Display;
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs()
+}
+
+/// A fraction in lowest terms, with the sign on the numerator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fraction {
+    pub numer: i128,
+    pub denom: i128,
+}
+
+impl Fraction {
+    pub fn new(numer: i128, denom: i128) -> Self {
+        assert!(denom != 0, "denominator should be nonzero");
+        let divisor = gcd(numer, denom) * denom.signum();
+        Fraction {
+            numer: numer / divisor,
+            denom: denom / divisor,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+}
+
+impl Display for Fraction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+/// A polynomial in `x` with rational coefficients, lowest power first.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Polynomial {
+    pub coeffs: Vec<Fraction>,
+}
+
+impl Polynomial {
+    /// Turns Newton's form, `sum of leading_diffs[j] * binomial(x, j)`, into an ordinary
+    /// polynomial. `None` if the numbers don't fit in an i128.
+    pub fn from_newton(leading_diffs: &[i128]) -> Option<Self> {
+        // binomial(x, j) is x(x-1)...(x-j+1) / j!, so put everything over the biggest j! there
+        // is and only divide at the end
+        let num_diffs = leading_diffs.len() as i128;
+        let denom = (1..num_diffs).try_fold(1_i128, i128::checked_mul)?;
+        let mut numers = vec![0_i128; leading_diffs.len()];
+        // x(x-1)...(x-j+1), lowest power first
+        let mut falling = vec![1_i128];
+        let mut factorial = 1_i128;
+        for (j, diff) in leading_diffs.iter().enumerate() {
+            if j > 0 {
+                let j = j as i128;
+                factorial *= j;
+                // multiply by (x - (j - 1))
+                let mut next = vec![0_i128; falling.len() + 1];
+                for (power, coeff) in falling.iter().enumerate() {
+                    next[power + 1] = next[power + 1].checked_add(*coeff)?;
+                    next[power] = next[power].checked_sub(coeff.checked_mul(j - 1)?)?;
+                }
+                falling = next;
+            }
+            let scale = diff.checked_mul(denom / factorial)?;
+            for (numer, coeff) in numers.iter_mut().zip(falling.iter()) {
+                *numer = numer.checked_add(coeff.checked_mul(scale)?)?;
+            }
+        }
+
+        let mut coeffs = numers
+            .into_iter()
+            .map(|numer| Fraction::new(numer, denom))
+            .collect::<Vec<_>>();
+        while coeffs.last().is_some_and(|coeff| coeff.numer == 0) {
+            coeffs.pop();
+        }
+        Some(Polynomial { coeffs })
+    }
+
+    /// `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    #[cfg(test)]
+    pub fn eval(&self, x: i128) -> Fraction {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(Fraction::new(0, 1), |acc, coeff| {
+                let numer = acc.numer * x * coeff.denom + coeff.numer * acc.denom;
+                Fraction::new(numer, acc.denom * coeff.denom)
+            })
+    }
+}
+
+impl Display for Polynomial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut terms = self
+            .coeffs
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, coeff)| coeff.numer != 0)
+            .peekable();
+        if terms.peek().is_none() {
+            return write!(f, "0");
+        }
+        let mut first = true;
+        for (power, coeff) in terms {
+            let sign = if coeff.numer < 0 { "-" } else { "+" };
+            if first {
+                if coeff.numer < 0 {
+                    write!(f, "-")?;
+                }
+            } else {
+                write!(f, " {} ", sign)?;
+            }
+            first = false;
+
+            let magnitude = Fraction::new(coeff.numer.abs(), coeff.denom);
+            // leave out a coefficient of 1, apart from on the constant
+            if power == 0 || magnitude != Fraction::new(1, 1) {
+                write!(f, "{}", magnitude)?;
+                if power > 0 {
+                    write!(f, " ")?;
+                }
+            }
+            match power {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{}", power)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_from_newton() {
+    // 1 3 6 10 15 21
+    let poly = Polynomial::from_newton(&[1, 2, 1]).unwrap();
+    assert_eq!(poly.to_string(), "1/2 x^2 + 3/2 x + 1");
+    assert_eq!(poly.degree(), Some(2));
+    // 10 13 16 21 30 45
+    let poly = Polynomial::from_newton(&[10, 3, 0, 2]).unwrap();
+    assert_eq!(poly.to_string(), "1/3 x^3 - x^2 + 11/3 x + 10");
+    assert_eq!(poly.eval(5), Fraction::new(45, 1));
+    assert_eq!(poly.eval(-1), Fraction::new(5, 1));
+
+    assert_eq!(Polynomial::from_newton(&[0, 3]).unwrap().to_string(), "3 x");
+    assert_eq!(
+        Polynomial::from_newton(&[-1, -1]).unwrap().to_string(),
+        "-x - 1"
+    );
+    let zero = Polynomial::from_newton(&[]).unwrap();
+    assert_eq!(zero.to_string(), "0");
+    assert_eq!(zero.degree(), None);
+    assert_eq!(Polynomial::from_newton(&[i128::MAX, 1, 1]), None);
+}
@@ -1,32 +1,64 @@
-use std::ops::Add;
-use std::ops::AddAssign;
-
-fn add_margin(s: &str) -> String {
-    let mut result = String::new();
-    let mut lines = s.lines();
-    let first_line = lines.next().unwrap();
-    let len = first_line.len();
-    let blank_line = &".".repeat(len + 2);
-    result.push_str(blank_line);
-    result.push('\n');
-
-    let first_line_padded = format!(".{}.", first_line);
-    result.push_str(&first_line_padded);
-    result.push('\n');
-    for line in lines {
-        let padded_line = format!(".{}.", line);
-        result.push_str(&padded_line);
-        result.push('\n');
-    }
-    result.push_str(blank_line);
-    result.push('\n');
-    result
-}
+mod render;
 
 #[derive(Clone)]
 struct PipeMaze {
     maze: Vec<Vec<PipeSection>>,
     start: (usize, usize),
+    /// the pipe hidden under the start, worked out from which way round it closes the loop
+    start_tile: PipeSection,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum MazeError {
+    /// a character that isn't a pipe, the ground or the start, at a line and column counting
+    /// from 1
+    UnknownTile {
+        line: usize,
+        column: usize,
+        char: char,
+    },
+    NoStart,
+    /// none of the pipes the start could be makes a loop
+    NoLoop {
+        start: (usize, usize),
+    },
+    /// more than one of the pipes the start could be makes a loop
+    AmbiguousStart {
+        start: (usize, usize),
+        candidates: Vec<PipeSection>,
+    },
+}
+
+impl std::fmt::Display for MazeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // as line and column numbers in the input, counting from 1
+        match self {
+            MazeError::UnknownTile { line, column, char } => write!(
+                f,
+                "line {}, column {}: {:?} isn't a pipe, ground or the start",
+                line, column, char
+            ),
+            MazeError::NoStart => write!(f, "there's no start tile S"),
+            MazeError::NoLoop { start } => write!(
+                f,
+                "the start at line {}, column {} isn't on a loop",
+                start.0 + 1,
+                start.1 + 1
+            ),
+            MazeError::AmbiguousStart { start, candidates } => {
+                write!(
+                    f,
+                    "the start at line {}, column {} closes a loop as any of",
+                    start.0 + 1,
+                    start.1 + 1
+                )?;
+                for candidate in candidates {
+                    write!(f, " {}", candidate)?;
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 impl PipeMaze {
@@ -38,17 +70,24 @@ impl PipeMaze {
         Self {
             maze: new_maze,
             start: self.start,
+            start_tile: self.start_tile,
         }
     }
 }
 
 impl std::str::FromStr for PipeMaze {
-    type Err = ();
+    type Err = MazeError;
 
     fn from_str(s: &str) -> Result<PipeMaze, Self::Err> {
-        let maze = Self::read_maze(s);
-        let start = Self::find_start(&maze);
-        Ok(PipeMaze { maze, start })
+        let maze = Self::read_maze(s)?;
+        let start = Self::find_start(&maze).ok_or(MazeError::NoStart)?;
+        let mut maze = PipeMaze {
+            maze,
+            start,
+            start_tile: PipeSection::Start,
+        };
+        maze.start_tile = maze.resolve_start_tile()?;
+        Ok(maze)
     }
 }
 
@@ -69,48 +108,300 @@ impl std::fmt::Display for PipeMaze {
 }
 
 impl PipeMaze {
-    pub fn pipe_section_at(&self, (row, col): (usize, usize)) -> PipeSection {
-        self.maze[row][col]
+    /// `None` if `(row, col)` is off the edge of the maze.
+    pub fn pipe_section_at(&self, (row, col): (usize, usize)) -> Option<PipeSection> {
+        self.maze.get(row)?.get(col).copied()
     }
 
-    fn start_exit_directions(&self) -> (Direction, Direction) {
+    /// The tile next to `(row, col)` in direction `dir`, or `None` if that's off the edge.
+    pub fn neighbor(&self, (row, col): (usize, usize), dir: Direction) -> Option<(usize, usize)> {
         use Direction::*;
-        let mut next_dir = Some(North);
-        let mut first = None;
-        let mut second = None;
-        while let Some(cur_dir) = next_dir {
-            let neighbor = self.start + cur_dir;
-            let neighbor_pipe = self.pipe_section_at(neighbor);
-            if neighbor_pipe.has_entrance_from(cur_dir.flip()) {
-                if first.is_none() {
-                    first = Some(cur_dir);
-                } else if second.is_none() {
-                    second = Some(cur_dir);
-                } else {
-                    panic!("Too many exit directions");
+        let neighbor = match dir {
+            North => (row.checked_sub(1)?, col),
+            South => (row + 1, col),
+            East => (row, col + 1),
+            West => (row, col.checked_sub(1)?),
+        };
+        self.pipe_section_at(neighbor).map(|_| neighbor)
+    }
+
+    /// Follows the pipe at `pos` on from where we came in heading `dir`, returning where it leads
+    /// and which way we'll be heading. `None` if there isn't a pipe there that we can get into
+    /// from that side, or if it leads off the edge.
+    fn try_follow(
+        &self,
+        pos: (usize, usize),
+        dir: Direction,
+    ) -> Option<((usize, usize), Direction)> {
+        let pipe = self.pipe_section_at(pos)?;
+        if !pipe.has_entrance_from(dir.flip()) {
+            return None;
+        }
+        let next_dir = pipe.exit_direction(dir.flip());
+        Some((self.neighbor(pos, next_dir)?, next_dir))
+    }
+
+    fn follow(&self, pos: (usize, usize), dir: Direction) -> ((usize, usize), Direction) {
+        self.try_follow(pos, dir)
+            .expect("the loop should carry on inside the maze")
+    }
+
+    /// Works out which pipe the start must be by trying each one and seeing which closes a loop,
+    /// so that stray pipes that happen to point at the start don't matter.
+    fn resolve_start_tile(&self) -> Result<PipeSection, MazeError> {
+        let mut candidates = Vec::new();
+        for (i, dir0) in Direction::all().enumerate() {
+            for dir1 in Direction::all().skip(i + 1) {
+                if self.closes_loop(dir0, dir1) {
+                    candidates.push(PipeSection::from_exit_directions(dir0, dir1));
                 }
             }
-            next_dir = cur_dir.next();
         }
-        (first.unwrap(), second.unwrap())
+        match candidates[..] {
+            [start_tile] => Ok(start_tile),
+            [] => Err(MazeError::NoLoop { start: self.start }),
+            _ => Err(MazeError::AmbiguousStart {
+                start: self.start,
+                candidates,
+            }),
+        }
     }
 
-    fn read_maze(maze_str: &str) -> Vec<Vec<PipeSection>> {
+    /// Whether leaving the start heading `dir0` comes back round to it from `dir1`.
+    fn closes_loop(&self, dir0: Direction, dir1: Direction) -> bool {
+        let Some(mut pos) = self.neighbor(self.start, dir0) else {
+            return false;
+        };
+        let mut dir = dir0;
+        // each pipe only has two ends, so the walk can't get stuck going round some other loop
+        while pos != self.start {
+            match self.try_follow(pos, dir) {
+                Some(next) => (pos, dir) = next,
+                None => return false,
+            }
+        }
+        dir == dir1.flip()
+    }
+
+    /// The directions out of the start, in the order North, East, South, West.
+    fn start_exit_directions(&self) -> (Direction, Direction) {
+        let mut exits = Direction::all().filter(|dir| self.start_tile.has_entrance_from(*dir));
+        (exits.next().unwrap(), exits.next().unwrap())
+    }
+
+    fn read_maze(maze_str: &str) -> Result<Vec<Vec<PipeSection>>, MazeError> {
         maze_str
             .lines()
-            .map(|line| line.chars().map(PipeSection::from_char).collect())
+            .enumerate()
+            .map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(|(col, c)| {
+                        PipeSection::from_char(c).ok_or(MazeError::UnknownTile {
+                            line: row + 1,
+                            column: col + 1,
+                            char: c,
+                        })
+                    })
+                    .collect()
+            })
             .collect()
     }
 
-    fn find_start(maze: &[Vec<PipeSection>]) -> (usize, usize) {
+    fn find_start(maze: &[Vec<PipeSection>]) -> Option<(usize, usize)> {
         for (row, line) in maze.iter().enumerate() {
             for (col, section) in line.iter().enumerate() {
                 if *section == PipeSection::Start {
-                    return (row, col);
+                    return Some((row, col));
                 }
             }
         }
-        panic!("No start found");
+        None
+    }
+}
+
+/// The loop of pipe that goes through the start.
+struct Loop {
+    /// every tile on the loop in the order they're connected, beginning at the start
+    positions: Vec<(usize, usize)>,
+    start_tile: PipeSection,
+    rows: usize,
+    cols: usize,
+}
+
+impl PipeMaze {
+    pub fn main_loop(&self) -> Loop {
+        let (mut dir, _) = self.start_exit_directions();
+        let mut positions = vec![self.start];
+        let mut pos = self.neighbor(self.start, dir).unwrap();
+        while pos != self.start {
+            positions.push(pos);
+            (pos, dir) = self.follow(pos, dir);
+        }
+        Loop {
+            positions,
+            start_tile: self.start_tile,
+            rows: self.maze.len(),
+            cols: self.maze.iter().map(Vec::len).max().unwrap_or_default(),
+        }
+    }
+}
+
+impl Loop {
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// How many steps it is to the furthest point of the loop from the start.
+    pub fn farthest(&self) -> usize {
+        self.len() / 2
+    }
+
+    /// The area inside the loop, taking the middle of each tile as the corners of the polygon,
+    /// using the trapezoid method of the [Shoelace formula](https://en.wikipedia.org/wiki/Shoelace_formula).
+    pub fn area(&self) -> usize {
+        let next = self.positions.iter().cycle().skip(1);
+        let twice_signed_area = self
+            .positions
+            .iter()
+            .zip(next)
+            .map(|(&(x0, y0), &(x1, y1))| {
+                let (x0, y0, x1, y1) = (x0 as isize, y0 as isize, x1 as isize, y1 as isize);
+                (y0 + y1) * (x0 - x1)
+            })
+            .sum::<isize>();
+        // the corners are all on whole numbers and the sides all go straight across or down, so
+        // this comes out even
+        twice_signed_area.unsigned_abs() / 2
+    }
+
+    /// How many tiles the loop encloses, using [Pick's theorem](https://en.wikipedia.org/wiki/Pick%27s_theorem):
+    /// the area `A` is `i + b/2 - 1`, where `b` is the number of tiles on the loop.
+    pub fn interior_count(&self) -> usize {
+        self.area() + 1 - self.len() / 2
+    }
+
+    /// Whether each tile in the maze is on the loop.
+    pub fn membership(&self) -> Vec<Vec<bool>> {
+        let mut on_loop = vec![vec![false; self.cols]; self.rows];
+        for &(row, col) in self.positions.iter() {
+            on_loop[row][col] = true;
+        }
+        on_loop
+    }
+}
+
+impl Loop {
+    /// The loop drawn at three times the size, so that each tile becomes a 3×3 block with the
+    /// pipe running through the middle. That leaves gaps between pipes that are side by side,
+    /// for squeezing through.
+    pub fn upscale(&self) -> Vec<Vec<bool>> {
+        let mut pipe = vec![vec![false; self.cols * 3]; self.rows * 3];
+        let next = self.positions.iter().cycle().skip(1);
+        for (&(r0, c0), &(r1, c1)) in self.positions.iter().zip(next) {
+            // from the middle of one tile to the middle of the next
+            let rows = (r0.min(r1) * 3 + 1)..=(r0.max(r1) * 3 + 1);
+            let cols = (c0.min(c1) * 3 + 1)..=(c0.max(c1) * 3 + 1);
+            for row in &mut pipe[rows] {
+                row[cols.clone()].fill(true);
+            }
+        }
+        pipe
+    }
+
+    /// Which tiles are inside the loop, found by flooding the upscaled maze from the outside
+    /// and seeing which tiles the water never gets to the middle of.
+    pub fn flood_fill_interior(&self) -> Vec<Vec<bool>> {
+        let pipe = self.upscale();
+        // no pipe can reach the very edge of the upscaled maze, so the water can get all the way
+        // round the outside from the corner
+        let mut wet = vec![vec![false; self.cols * 3]; self.rows * 3];
+        let mut to_visit = vec![(0, 0)];
+        while let Some((r, c)) = to_visit.pop() {
+            if pipe[r][c] || wet[r][c] {
+                continue;
+            }
+            wet[r][c] = true;
+            if r > 0 {
+                to_visit.push((r - 1, c));
+            }
+            if r + 1 < pipe.len() {
+                to_visit.push((r + 1, c));
+            }
+            if c > 0 {
+                to_visit.push((r, c - 1));
+            }
+            if c + 1 < pipe[r].len() {
+                to_visit.push((r, c + 1));
+            }
+        }
+
+        (0..self.rows)
+            .map(|r| {
+                (0..self.cols)
+                    .map(|c| !pipe[r * 3 + 1][c * 3 + 1] && !wet[r * 3 + 1][c * 3 + 1])
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_interior_methods_agree() {
+    for (input, expected_tiles) in [
+        (include_str!("sample1a.txt"), 1),
+        (include_str!("sample1b.txt"), 1),
+        (include_str!("sample2a.txt"), 4),
+        (include_str!("sample2b.txt"), 8),
+        (include_str!("sample2c.txt"), 10),
+        (include_str!("my_input.txt"), 357),
+        ("S7\nLJ", 0),
+    ] {
+        let maze: PipeMaze = input.parse().unwrap();
+        let main_loop = maze.main_loop();
+        let scanned = scan_interior(&maze, &main_loop);
+        let flooded = main_loop.flood_fill_interior();
+        assert_eq!(scanned, flooded);
+        let num_inside = flooded.concat().iter().filter(|x| **x).count();
+        assert_eq!(num_inside, expected_tiles);
+        assert_eq!(main_loop.interior_count(), expected_tiles);
+    }
+
+    // the 3×3 blocks leave a gap between pipes that are side by side
+    let maze: PipeMaze = include_str!("sample2b.txt").parse().unwrap();
+    let upscaled = maze.main_loop().upscale();
+    assert_eq!(
+        upscaled[4][18..24],
+        [false, true, false, false, true, false]
+    );
+}
+
+#[test]
+fn test_main_loop() {
+    let maze: PipeMaze = include_str!("sample1b.txt").parse().unwrap();
+    let main_loop = maze.main_loop();
+    assert_eq!(main_loop.start_tile, PipeSection::SE);
+    assert_eq!(main_loop.len(), 16);
+    assert_eq!(main_loop.positions[..3], [maze.start, (2, 1), (1, 1)]);
+    assert_eq!(main_loop.farthest(), 8);
+    assert_eq!(
+        main_loop
+            .membership()
+            .concat()
+            .iter()
+            .filter(|x| **x)
+            .count(),
+        16
+    );
+    assert_eq!(main_loop.interior_count(), 1);
+
+    for (input, expected_tiles) in [
+        (include_str!("sample2a.txt"), 4),
+        (include_str!("sample2b.txt"), 8),
+        (include_str!("sample2c.txt"), 10),
+    ] {
+        let maze: PipeMaze = input.parse().unwrap();
+        assert_eq!(maze.main_loop().interior_count(), expected_tiles);
     }
 }
 
@@ -144,18 +435,18 @@ impl PipeSection {
         }
     }
 
-    fn from_char(c: char) -> PipeSection {
+    fn from_char(c: char) -> Option<PipeSection> {
         match c {
-            '|' => PipeSection::NS,
-            '-' => PipeSection::EW,
-            'L' => PipeSection::NE,
-            'J' => PipeSection::NW,
-            '7' => PipeSection::SW,
-            'F' => PipeSection::SE,
-            '.' => PipeSection::Ground,
-            'S' => PipeSection::Start,
-            'X' => PipeSection::Marker,
-            _ => panic!("Unknown pipe section: {}", c),
+            '|' => Some(PipeSection::NS),
+            '-' => Some(PipeSection::EW),
+            'L' => Some(PipeSection::NE),
+            'J' => Some(PipeSection::NW),
+            '7' => Some(PipeSection::SW),
+            'F' => Some(PipeSection::SE),
+            '.' => Some(PipeSection::Ground),
+            'S' => Some(PipeSection::Start),
+            'X' => Some(PipeSection::Marker),
+            _ => None,
         }
     }
 
@@ -219,6 +510,7 @@ impl PipeSection {
         matches!(self, NE | NW | SW | SE)
     }
 
+    #[allow(dead_code)]
     fn is_straight(&self) -> bool {
         use PipeSection::*;
         matches!(self, NS | EW)
@@ -229,6 +521,7 @@ impl PipeSection {
         matches!(self, NS)
     }
 
+    #[allow(dead_code)]
     fn is_horizontal(&self) -> bool {
         use PipeSection::*;
         matches!(self, EW)
@@ -244,6 +537,11 @@ enum Direction {
 }
 
 impl Direction {
+    /// North, East, South and West, in that order.
+    pub fn all() -> impl Iterator<Item = Direction> {
+        std::iter::successors(Some(Direction::North), Direction::next)
+    }
+
     pub fn next(&self) -> Option<Direction> {
         use Direction::*;
         match self {
@@ -265,30 +563,9 @@ impl Direction {
     }
 }
 
-impl AddAssign<Direction> for (usize, usize) {
-    fn add_assign(&mut self, rhs: Direction) {
-        *self = *self + rhs;
-    }
-}
-
-impl Add<Direction> for (usize, usize) {
-    type Output = (usize, usize);
-
-    fn add(self, rhs: Direction) -> Self::Output {
-        use Direction::*;
-        let (row, col) = self;
-        match rhs {
-            North => (row - 1, col),
-            South => (row + 1, col),
-            East => (row, col + 1),
-            West => (row, col - 1),
-        }
-    }
-}
-
 impl From<char> for PipeSection {
     fn from(c: char) -> PipeSection {
-        PipeSection::from_char(c)
+        PipeSection::from_char(c).unwrap_or_else(|| panic!("Unknown pipe section: {}", c))
     }
 }
 
@@ -332,85 +609,23 @@ fn part1() {
     // let (input, expected_steps) = (include_str!("sample1a.txt"), Some(4));
     // let (input, expected_steps) = (include_str!("sample1b.txt"), Some(8));
     let (input, expected_steps) = (include_str!("my_input.txt"), Some(6931));
-    let maze: PipeMaze = add_margin(input).parse().unwrap();
-
-    // travel directions
-    let (mut dir0, mut dir1) = maze.start_exit_directions();
-    let mut pos0 = maze.start + dir0;
-    let mut pos1 = maze.start + dir1;
-    let mut num_steps = 1;
-    while pos0 != pos1 {
-        // println!("Num steps: {}", num_steps);
-        // println!("{:#}", maze.with_location(pos0).with_location(pos1));
-        // println!();
-
-        let pipe0 = maze.pipe_section_at(pos0);
-        let pipe1 = maze.pipe_section_at(pos1);
-        let next_dir0 = pipe0.exit_direction(dir0.flip());
-        let next_dir1 = pipe1.exit_direction(dir1.flip());
-
-        pos0 += next_dir0;
-        pos1 += next_dir1;
-        dir0 = next_dir0;
-        dir1 = next_dir1;
-        num_steps += 1;
-    }
+    let maze: PipeMaze = input
+        .parse()
+        .unwrap_or_else(|err| panic!("couldn't load the maze: {}", err));
+
+    let num_steps = maze.main_loop().farthest();
     println!("part 1 num steps: {}", num_steps);
     if let Some(expected_steps) = expected_steps {
         assert_eq!(num_steps, expected_steps);
     }
 }
 
-/// Calculates the area of a polygon using the trapezoid method of the [Shoelace formula](https://en.wikipedia.org/wiki/Shoelace_formula)
-fn polygon_area_trapezoid(path: &[(usize, usize)]) -> f64 {
-    let signed_area = path
-        .windows(2)
-        .map(|pair| {
-            let (x0, y0) = pair[0];
-            let (x1, y1) = pair[1];
-            let x0 = x0 as isize;
-            let x1 = x1 as isize;
-            let y0 = y0 as isize;
-            let y1 = y1 as isize;
-            (y0 + y1) * (x0 - x1)
-        })
-        .sum::<isize>()
-        / 2;
-    signed_area.abs() as f64
-}
-
 fn part2_picks_theorum(input: &str, expected_contained_tiles: Option<usize>) {
-    let maze: PipeMaze = add_margin(input).parse().unwrap();
-
-    // travel directions
-    let (mut dir, _) = maze.start_exit_directions();
-
-    // follow the maze, counting the length of the path
-    let mut route = Vec::new();
-    route.push(maze.start);
-    let mut pos = maze.start + dir;
-    while pos != maze.start {
-        route.push(pos);
-        let pipe = maze.pipe_section_at(pos);
-        let next_dir = pipe.exit_direction(dir.flip());
-        pos += next_dir;
-        dir = next_dir;
-    }
-    route.push(maze.start); // to complete the loop, need to return to the start
-    let route = route;
-
-    // Use Pick's theorem to count the number of tiles inside the polygon.
-    // https://en.wikipedia.org/wiki/Pick%27s_theorem
-    // First we need to total area of the polygon formed by the route of the
-    // pipes (A). We will use shoelace formula to calculate this.
-    let total_area = polygon_area_trapezoid(&route);
-    // The number of segments in the pipe route is the number of boundary points (b)
-    // we added one extra segment to close the loop, so we subtract one
-    let b = route.len() - 1;
-    // i = A - (b/2) + 1
-    #[allow(non_snake_case)]
-    let A = total_area as usize; // we know the area must be an integer because we only have rectangles
-    let internal_points_picks = A - (b / 2) + 1;
+    let maze: PipeMaze = input
+        .parse()
+        .unwrap_or_else(|err| panic!("couldn't load the maze: {}", err));
+
+    let internal_points_picks = maze.main_loop().interior_count();
 
     println!("part 2 internal points pick's: {}", internal_points_picks);
     if let Some(expected_tiles) = expected_contained_tiles {
@@ -418,45 +633,24 @@ fn part2_picks_theorum(input: &str, expected_contained_tiles: Option<usize>) {
     }
 }
 
-/// Count internal tiles by scanning the maze. This also identifies the internal
-/// tiles, rather than just counting them like using Pick's theorem.
-fn part2_scanlines(input: &str, expected_contained_tiles: Option<usize>) {
-    let maze: PipeMaze = add_margin(input).parse().unwrap();
-
-    // Let's mark the path of the pipe we're interested in
-    let (mut dir, _) = maze.start_exit_directions();
-    let mut loop_marked_maze = maze.clone();
-    loop_marked_maze.maze[maze.start.0][maze.start.1] = PipeSection::Marker;
-    let mut pos = maze.start + dir;
-    while pos != maze.start {
-        let pipe = maze.pipe_section_at(pos);
-        let next_dir = pipe.exit_direction(dir.flip());
-        loop_marked_maze.maze[pos.0][pos.1] = PipeSection::Marker;
-        pos += next_dir;
-        dir = next_dir;
-    }
-
-    // Now that we know where our pipe is, we scan each line of the maze,
-    // marking which tiles are contained within our pipe's loop.
-    let mut inside_outside_maze = maze.clone();
-    let loop_marked_maze = loop_marked_maze;
-    let (dir0, dir1) = maze.start_exit_directions();
-    let start_tile = PipeSection::from_exit_directions(dir0, dir1);
-    let mut num_internal_tiles_scanned = 0;
+/// Which tiles are inside the loop, found by scanning each line of the maze west to east and
+/// flipping between outside and inside every time we cross the loop.
+fn scan_interior(maze: &PipeMaze, main_loop: &Loop) -> Vec<Vec<bool>> {
+    let on_loop = main_loop.membership();
+    let mut inside_tiles = vec![vec![false; main_loop.cols]; main_loop.rows];
     for (r, row) in maze.maze.iter().enumerate() {
-        use PipeSection::*;
         let mut inside = false;
         let mut prev_unmatched_corner = None;
 
         // scan west to east
         for (c, tile) in row.iter().enumerate() {
             let tile = if (r, c) == maze.start {
-                start_tile
+                main_loop.start_tile
             } else {
                 *tile
             };
 
-            if loop_marked_maze.pipe_section_at((r, c)) == Marker {
+            if on_loop[r][c] {
                 if tile.is_vertical() {
                     inside ^= true;
                 } else if tile.is_corner() {
@@ -470,17 +664,38 @@ fn part2_scanlines(input: &str, expected_contained_tiles: Option<usize>) {
                         prev_unmatched_corner = Some(tile);
                     }
                 }
-            } else if inside {
+            } else {
+                inside_tiles[r][c] = inside;
+            }
+        }
+    }
+    inside_tiles
+}
+
+/// Count internal tiles by scanning the maze. This also identifies the internal
+/// tiles, rather than just counting them like using Pick's theorem.
+fn part2_scanlines(input: &str, expected_contained_tiles: Option<usize>) {
+    let maze: PipeMaze = input
+        .parse()
+        .unwrap_or_else(|err| panic!("couldn't load the maze: {}", err));
+
+    // Let's mark the path of the pipe we're interested in
+    let main_loop = maze.main_loop();
+    let on_loop = main_loop.membership();
+
+    let inside = scan_interior(&maze, &main_loop);
+    let mut inside_outside_maze = maze.clone();
+    let mut num_internal_tiles_scanned = 0;
+    for (r, row) in inside.iter().enumerate() {
+        for (c, tile_inside) in row.iter().enumerate() {
+            if *tile_inside {
                 num_internal_tiles_scanned += 1;
-                inside_outside_maze.maze[r][c] = bool_to_pipe_section(inside);
+                inside_outside_maze.maze[r][c] = bool_to_pipe_section(*tile_inside);
             }
         }
     }
     println!("scanned map:");
-    println!(
-        "{}",
-        colorize_maze(&maze, &loop_marked_maze, &inside_outside_maze)
-    );
+    println!("{}", colorize_maze(&maze, &on_loop, &inside_outside_maze));
     println!(
         "internal tiles using scanlines: {}",
         num_internal_tiles_scanned
@@ -490,7 +705,104 @@ fn part2_scanlines(input: &str, expected_contained_tiles: Option<usize>) {
     }
 }
 
-fn colorize_maze(original: &PipeMaze, marked: &PipeMaze, inside_outside: &PipeMaze) -> String {
+fn part2_flood_fill(input: &str, expected_contained_tiles: Option<usize>) {
+    let maze: PipeMaze = input
+        .parse()
+        .unwrap_or_else(|err| panic!("couldn't load the maze: {}", err));
+    let inside = maze.main_loop().flood_fill_interior();
+    let num_internal_tiles_flooded = inside.concat().iter().filter(|x| **x).count();
+    println!(
+        "internal tiles using flood fill: {}",
+        num_internal_tiles_flooded
+    );
+    if let Some(expected_contained_tiles) = expected_contained_tiles {
+        assert_eq!(num_internal_tiles_flooded, expected_contained_tiles);
+    }
+}
+
+#[test]
+fn test_loop_along_the_edges() {
+    // the loop runs right around the edge, which used to need a margin adding
+    let input = indoc::indoc! {"
+        S--7
+        |..|
+        L--J"};
+    let maze: PipeMaze = input
+        .parse()
+        .unwrap_or_else(|err| panic!("couldn't load the maze: {}", err));
+    assert_eq!(maze.start, (0, 0));
+    assert_eq!(maze.neighbor((0, 0), Direction::North), None);
+    assert_eq!(maze.neighbor((0, 0), Direction::West), None);
+    assert_eq!(maze.neighbor((2, 3), Direction::South), None);
+    assert_eq!(maze.neighbor((2, 3), Direction::East), None);
+    assert_eq!(maze.neighbor((2, 3), Direction::West), Some((2, 2)));
+    assert_eq!(maze.pipe_section_at((3, 0)), None);
+    assert_eq!(
+        maze.start_exit_directions(),
+        (Direction::East, Direction::South)
+    );
+    part2_picks_theorum(input, Some(2));
+    part2_scanlines(input, Some(2));
+    part2_flood_fill(input, Some(2));
+}
+
+#[test]
+fn test_resolve_start_tile() {
+    // the pipes above and to the left of S point at it, but aren't part of the loop
+    let maze: PipeMaze = indoc::indoc! {"
+        ..|..
+        -S-7.
+        .|.|-
+        .L-J."}
+    .parse()
+    .unwrap();
+    assert_eq!(maze.start_tile, PipeSection::SE);
+    assert_eq!(
+        maze.start_exit_directions(),
+        (Direction::East, Direction::South)
+    );
+
+    // S closes a loop to the north east and another to the south west
+    let err = indoc::indoc! {"
+        .....
+        ..F7.
+        .FSJ.
+        .LJ..
+        ....."}
+    .parse::<PipeMaze>()
+    .err();
+    assert_eq!(
+        err,
+        Some(MazeError::AmbiguousStart {
+            start: (2, 2),
+            candidates: vec![PipeSection::NE, PipeSection::SW]
+        })
+    );
+    assert_eq!(
+        err.unwrap().to_string(),
+        "the start at line 3, column 3 closes a loop as any of L 7"
+    );
+
+    let err = "S-7\n|.|\nL-.".parse::<PipeMaze>().err();
+    assert_eq!(err, Some(MazeError::NoLoop { start: (0, 0) }));
+    let err = "F-7\n|.|\nL-J".parse::<PipeMaze>().err();
+    assert_eq!(err, Some(MazeError::NoStart));
+    let err = "S-7\n|.|\nL-?".parse::<PipeMaze>().err();
+    assert_eq!(
+        err,
+        Some(MazeError::UnknownTile {
+            line: 3,
+            column: 3,
+            char: '?'
+        })
+    );
+    assert_eq!(
+        err.unwrap().to_string(),
+        "line 3, column 3: '?' isn't a pipe, ground or the start"
+    );
+}
+
+fn colorize_maze(original: &PipeMaze, on_loop: &[Vec<bool>], inside_outside: &PipeMaze) -> String {
     use inline_colorization::*;
     let mut result = String::new();
     for (r, orig_row) in original.maze.iter().enumerate() {
@@ -499,11 +811,11 @@ fn colorize_maze(original: &PipeMaze, marked: &PipeMaze, inside_outside: &PipeMa
                 result.push_str(color_bright_red);
                 result.push_str(&format!("{:#}", tile));
                 result.push_str(color_reset);
-            } else if marked.pipe_section_at((r, c)) == PipeSection::Marker {
+            } else if on_loop[r][c] {
                 result.push_str(color_bright_green);
                 result.push_str(&format!("{:#}", tile));
                 result.push_str(color_reset);
-            } else if inside_outside.pipe_section_at((r, c)) == PipeSection::Inside {
+            } else if inside_outside.pipe_section_at((r, c)) == Some(PipeSection::Inside) {
                 result.push_str(color_bright_magenta);
                 result.push_str(bg_cyan);
                 // result.push_str(&format!("{:#}", tile));
@@ -536,7 +848,36 @@ fn do_corners_form_u(left: PipeSection, right: PipeSection) -> bool {
     }
 }
 
+/// Draws the maze as an SVG, and as a PNG too if there's somewhere to put one.
+fn export(svg_path: &str, png_path: Option<&str>) {
+    let maze: PipeMaze = include_str!("my_input.txt")
+        .parse()
+        .unwrap_or_else(|err| panic!("couldn't load the maze: {}", err));
+    let main_loop = maze.main_loop();
+    let inside = main_loop.flood_fill_interior();
+
+    let svg = render::to_svg(&maze, &main_loop, &inside);
+    std::fs::write(svg_path, svg)
+        .unwrap_or_else(|err| panic!("couldn't write {}: {}", svg_path, err));
+    println!("wrote {}", svg_path);
+    if let Some(png_path) = png_path {
+        let file = std::fs::File::create(png_path)
+            .unwrap_or_else(|err| panic!("couldn't create {}: {}", png_path, err));
+        render::write_png(std::io::BufWriter::new(file), &main_loop, &inside)
+            .unwrap_or_else(|err| panic!("couldn't write {}: {}", png_path, err));
+        println!("wrote {}", png_path);
+    }
+}
+
 fn main() {
+    // `cargo run -- export <svg file> [<png file>]` draws the maze instead of solving it
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.first().is_some_and(|arg| arg == "export") {
+        let svg_path = args.get(1).expect("should have found `export <svg file>`");
+        export(svg_path, args.get(2).map(String::as_str));
+        return;
+    }
+
     part1();
 
     // let (input, expected_contained_tiles) = (include_str!("sample2a.txt"), Some(4));
@@ -552,4 +893,5 @@ fn main() {
     // );
     part2_picks_theorum(input, expected_contained_tiles);
     part2_scanlines(input, expected_contained_tiles);
+    part2_flood_fill(input, expected_contained_tiles);
 }
@@ -0,0 +1,173 @@
+use std::io::Write;
+
+use crate::{Loop, PipeMaze, PipeSection};
+
+/// How many SVG units each tile takes up.
+const TILE_SIZE: usize = 12;
+
+/// How many pixels each cell of the upscaled maze takes up in a PNG, so each tile is three times
+/// this across.
+const PIXELS_PER_CELL: usize = 2;
+
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const INSIDE: [u8; 3] = [159, 217, 230];
+const LOOP: [u8; 3] = [34, 139, 34];
+const START: [u8; 3] = [220, 20, 60];
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Draws the maze as an SVG: the stray pipes as their box-drawing characters, the interior
+/// shaded in, the loop as one line all the way round, and a dot on the start.
+pub fn to_svg(maze: &PipeMaze, main_loop: &Loop, inside: &[Vec<bool>]) -> String {
+    let (width, height) = (main_loop.cols * TILE_SIZE, main_loop.rows * TILE_SIZE);
+    let middle = |(row, col): (usize, usize)| {
+        (
+            col * TILE_SIZE + TILE_SIZE / 2,
+            row * TILE_SIZE + TILE_SIZE / 2,
+        )
+    };
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+        width, height
+    );
+    svg += &format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+        width,
+        height,
+        hex(BACKGROUND)
+    );
+
+    svg += &format!("<g fill=\"{}\">\n", hex(INSIDE));
+    for (row, line) in inside.iter().enumerate() {
+        for (col, _) in line.iter().enumerate().filter(|(_, inside)| **inside) {
+            svg += &format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>\n",
+                col * TILE_SIZE,
+                row * TILE_SIZE,
+                TILE_SIZE,
+                TILE_SIZE
+            );
+        }
+    }
+    svg += "</g>\n";
+
+    let on_loop = main_loop.membership();
+    svg += &format!(
+        "<g font-family=\"monospace\" font-size=\"{}\" fill=\"gray\" text-anchor=\"middle\" dominant-baseline=\"central\">\n",
+        TILE_SIZE
+    );
+    for (row, line) in maze.maze.iter().enumerate() {
+        for (col, tile) in line.iter().enumerate() {
+            if on_loop[row][col] || matches!(tile, PipeSection::Ground | PipeSection::Start) {
+                continue;
+            }
+            let (x, y) = middle((row, col));
+            svg += &format!("<text x=\"{}\" y=\"{}\">{:#}</text>\n", x, y, tile);
+        }
+    }
+    svg += "</g>\n";
+
+    // back to the start at the end to close the loop
+    let points = main_loop
+        .positions
+        .iter()
+        .chain(main_loop.positions.first())
+        .map(|pos| {
+            let (x, y) = middle(*pos);
+            format!("{},{}", x, y)
+        })
+        .collect::<Vec<_>>();
+    svg += &format!(
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\"/>\n",
+        points.join(" "),
+        hex(LOOP),
+        TILE_SIZE / 4
+    );
+    let (x, y) = middle(maze.start);
+    svg += &format!(
+        "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>\n",
+        x,
+        y,
+        TILE_SIZE / 3,
+        hex(START)
+    );
+    svg += "</svg>\n";
+    svg
+}
+
+/// Draws the loop as a PNG from its upscaled version, with the interior shaded in and the whole
+/// of the start tile in red so it stands out. Unlike the SVG, it leaves out the stray pipes.
+pub fn write_png<W: Write>(
+    writer: W,
+    main_loop: &Loop,
+    inside: &[Vec<bool>],
+) -> Result<(), png::EncodingError> {
+    let upscaled = main_loop.upscale();
+    let start = main_loop.positions[0];
+    let (width, height) = (
+        main_loop.cols * 3 * PIXELS_PER_CELL,
+        main_loop.rows * 3 * PIXELS_PER_CELL,
+    );
+    let mut data = Vec::with_capacity(width * height * 3);
+    for y in 0..height {
+        for x in 0..width {
+            let (row, col) = (y / PIXELS_PER_CELL, x / PIXELS_PER_CELL);
+            let tile = (row / 3, col / 3);
+            let color = if tile == start {
+                START
+            } else if upscaled[row][col] {
+                LOOP
+            } else if inside[tile.0][tile.1] {
+                INSIDE
+            } else {
+                BACKGROUND
+            };
+            data.extend_from_slice(&color);
+        }
+    }
+
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&data)
+}
+
+#[test]
+fn test_to_svg() {
+    let maze: PipeMaze = indoc::indoc! {"
+        S-7-
+        |.|.
+        L-J."}
+    .parse()
+    .unwrap();
+    let main_loop = maze.main_loop();
+    let svg = to_svg(&maze, &main_loop, &main_loop.flood_fill_interior());
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"48\" height=\"36\""));
+    assert!(svg.contains("<rect x=\"12\" y=\"12\" width=\"12\" height=\"12\"/>"));
+    assert!(svg.contains("<polyline points=\"6,6 18,6 30,6 30,18 30,30 18,30 6,30 6,18 6,6\""));
+    assert!(svg.contains("<circle cx=\"6\" cy=\"6\""));
+    // only the stray pipe is drawn as text
+    assert_eq!(svg.matches("<text").count(), 1);
+    assert!(svg.contains("<text x=\"42\" y=\"6\">─</text>"));
+}
+
+#[test]
+fn test_write_png() {
+    let maze: PipeMaze = "S7\nLJ".parse().unwrap();
+    let main_loop = maze.main_loop();
+    let mut bytes = Vec::new();
+    write_png(&mut bytes, &main_loop, &main_loop.flood_fill_interior()).unwrap();
+
+    let mut reader = png::Decoder::new(&bytes[..]).read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!((info.width, info.height), (12, 12));
+    let pixel = |x: usize, y: usize| &pixels[(y * 12 + x) * 3..(y * 12 + x) * 3 + 3];
+    assert_eq!(pixel(0, 0), START);
+    assert_eq!(pixel(2, 2), START);
+    // the corner of the tile to the right of the start, and the middle of the one below it
+    assert_eq!(pixel(11, 0), BACKGROUND);
+    assert_eq!(pixel(2, 8), LOOP);
+}